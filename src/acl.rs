@@ -0,0 +1,196 @@
+// https://cloud.google.com/storage/docs/access-control/lists
+
+use std::fmt;
+use std::str::FromStr;
+
+// https://cloud.google.com/storage/docs/json_api/v1/objectAccessControls#resource
+#[serde_with::serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessControl {
+    pub bucket: String,
+    pub domain: Option<String>,
+    pub email: Option<String>,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub entity: Entity,
+    pub entity_id: Option<String>,
+    pub etag: Option<String>,
+    #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+    #[serde(default)]
+    pub generation: Option<u64>,
+    pub id: Option<String>,
+    pub object: Option<String>,
+    pub project_team: Option<ProjectTeam>,
+    pub role: Role,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTeam {
+    pub project_number: String,
+    pub team: Team,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Entity {
+    User(String),
+    Group(String),
+    Domain(String),
+    Project(Team, String),
+    AllUsers,
+    AllAuthenticatedUsers,
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User(value) => write!(f, "user-{value}"),
+            Self::Group(value) => write!(f, "group-{value}"),
+            Self::Domain(value) => write!(f, "domain-{value}"),
+            Self::Project(team, value) => write!(f, "project-{}-{value}", team.as_str()),
+            Self::AllUsers => f.write_str("allUsers"),
+            Self::AllAuthenticatedUsers => f.write_str("allAuthenticatedUsers"),
+        }
+    }
+}
+
+impl FromStr for Entity {
+    type Err = InvalidEntity;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allUsers" => Ok(Self::AllUsers),
+            "allAuthenticatedUsers" => Ok(Self::AllAuthenticatedUsers),
+            _ => {
+                if let Some(value) = s.strip_prefix("user-") {
+                    Ok(Self::User(value.to_owned()))
+                } else if let Some(value) = s.strip_prefix("group-") {
+                    Ok(Self::Group(value.to_owned()))
+                } else if let Some(value) = s.strip_prefix("domain-") {
+                    Ok(Self::Domain(value.to_owned()))
+                } else if let Some(value) = s.strip_prefix("project-") {
+                    let (team, value) = value.split_once('-').ok_or(InvalidEntity)?;
+                    let team = match team {
+                        "owners" => Team::Owners,
+                        "editors" => Team::Editors,
+                        "viewers" => Team::Viewers,
+                        _ => return Err(InvalidEntity),
+                    };
+                    Ok(Self::Project(team, value.to_owned()))
+                } else {
+                    Err(InvalidEntity)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid entity")]
+pub struct InvalidEntity;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Team {
+    Owners,
+    Editors,
+    Viewers,
+}
+
+impl Team {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Owners => "owners",
+            Self::Editors => "editors",
+            Self::Viewers => "viewers",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Role {
+    Owner,
+    Reader,
+    // buckets only
+    Writer,
+}
+
+// https://cloud.google.com/storage/docs/access-control/lists#predefined-acl
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PredefinedAcl {
+    AuthenticatedRead,
+    BucketOwnerFullControl,
+    BucketOwnerRead,
+    Private,
+    ProjectPrivate,
+    PublicRead,
+    // buckets only
+    PublicReadWrite,
+}
+
+impl PredefinedAcl {
+    // value of the `predefinedAcl` query parameter in the JSON API
+    pub fn as_json_str(&self) -> &'static str {
+        match self {
+            Self::AuthenticatedRead => "authenticatedRead",
+            Self::BucketOwnerFullControl => "bucketOwnerFullControl",
+            Self::BucketOwnerRead => "bucketOwnerRead",
+            Self::Private => "private",
+            Self::ProjectPrivate => "projectPrivate",
+            Self::PublicRead => "publicRead",
+            Self::PublicReadWrite => "publicReadWrite",
+        }
+    }
+
+    // value of the `x-goog-acl` header in the XML API
+    pub fn as_xml_str(&self) -> &'static str {
+        match self {
+            Self::AuthenticatedRead => "authenticated-read",
+            Self::BucketOwnerFullControl => "bucket-owner-full-control",
+            Self::BucketOwnerRead => "bucket-owner-read",
+            Self::Private => "private",
+            Self::ProjectPrivate => "project-private",
+            Self::PublicRead => "public-read",
+            Self::PublicReadWrite => "public-read-write",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entity, Team};
+
+    #[test]
+    fn test_entity() {
+        for (s, entity) in [
+            (
+                "user-liz@example.com",
+                Entity::User("liz@example.com".to_owned()),
+            ),
+            (
+                "group-example@googlegroups.com",
+                Entity::Group("example@googlegroups.com".to_owned()),
+            ),
+            (
+                "domain-example.com",
+                Entity::Domain("example.com".to_owned()),
+            ),
+            (
+                "project-owners-123412341234",
+                Entity::Project(Team::Owners, "123412341234".to_owned()),
+            ),
+            (
+                "project-viewers-my-project",
+                Entity::Project(Team::Viewers, "my-project".to_owned()),
+            ),
+            ("allUsers", Entity::AllUsers),
+            ("allAuthenticatedUsers", Entity::AllAuthenticatedUsers),
+        ] {
+            assert_eq!(s.parse::<Entity>().unwrap(), entity);
+            assert_eq!(entity.to_string(), s);
+        }
+        assert!("project-admins-123412341234".parse::<Entity>().is_err());
+        assert!("someone".parse::<Entity>().is_err());
+    }
+}
@@ -1,4 +1,6 @@
+pub mod iam_credentials;
 pub mod json;
+pub mod metadata;
 pub mod xml;
 
 pub use http_extra::check_status::StatusError;
@@ -15,7 +17,29 @@ pub enum Error<S, B> {
     Service(S),
     #[error(transparent)]
     Status(StatusError),
+    #[error(transparent)]
+    XmlDe(quick_xml::DeError),
+    #[error(transparent)]
+    XmlSe(quick_xml::SeError),
+}
+
+fn query<'a, I>(mut uri: String, query: I) -> String
+where
+    I: IntoIterator<Item = (&'a str, String)>,
+{
+    let mut separator = if uri.contains('?') { '&' } else { '?' };
+    for (key, value) in query {
+        uri.push(separator);
+        uri.push_str(key);
+        uri.push('=');
+        uri.extend(percent_encoding::utf8_percent_encode(
+            &value,
+            percent_encoding::NON_ALPHANUMERIC,
+        ));
+        separator = '&';
+    }
+    uri
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "yup-oauth2"))]
 mod tests;
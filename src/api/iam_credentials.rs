@@ -0,0 +1,14 @@
+// https://cloud.google.com/iam/docs/reference/credentials/rest
+
+pub mod generate_access_token;
+pub mod sign_blob;
+
+use super::json::{send, Send};
+use std::fmt;
+
+fn service_account_uri<N>(name: N) -> String
+where
+    N: fmt::Display,
+{
+    format!("https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/{name}")
+}
@@ -0,0 +1,73 @@
+// https://cloud.google.com/iam/docs/reference/credentials/rest/v1/projects.serviceAccounts/generateAccessToken
+
+use std::time::Duration;
+use time::OffsetDateTime;
+
+pub fn builder<N>(name: N, scope: Vec<String>) -> Builder
+where
+    N: Into<String>,
+{
+    Builder {
+        name: name.into(),
+        request: Request {
+            delegates: Vec::new(),
+            lifetime: None,
+            scope,
+        },
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Builder {
+    name: String,
+    request: Request,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self { name, request } = self;
+        let builder = http::Request::post(format!(
+            "{}:generateAccessToken",
+            super::service_account_uri(name),
+        ));
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    // projects/-/serviceAccounts/{email}
+    pub fn delegates(mut self, delegates: Vec<String>) -> Self {
+        self.request.delegates = delegates;
+        self
+    }
+
+    // 1 hour by default, up to 12 hours if the organization allows it
+    pub fn lifetime(mut self, lifetime: Duration) -> Self {
+        self.request.lifetime = Some(format!("{}s", lifetime.as_secs()));
+        self
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Request {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub delegates: Vec<String>,
+    // a google.protobuf.Duration, e.g. "3600s"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifetime: Option<String>,
+    pub scope: Vec<String>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub access_token: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expire_time: OffsetDateTime,
+}
@@ -0,0 +1,60 @@
+// https://cloud.google.com/iam/docs/reference/credentials/rest/v1/projects.serviceAccounts/signBlob
+
+pub fn builder<N>(name: N, payload: Vec<u8>) -> Builder
+where
+    N: Into<String>,
+{
+    Builder {
+        name: name.into(),
+        request: Request {
+            delegates: Vec::new(),
+            payload,
+        },
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Builder {
+    name: String,
+    request: Request,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self { name, request } = self;
+        let builder = http::Request::post(format!("{}:signBlob", super::service_account_uri(name)));
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    // projects/-/serviceAccounts/{email}
+    pub fn delegates(mut self, delegates: Vec<String>) -> Self {
+        self.request.delegates = delegates;
+        self
+    }
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Request {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub delegates: Vec<String>,
+    #[serde_as(as = "serde_with::base64::Base64")]
+    pub payload: Vec<u8>,
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub key_id: String,
+    #[serde_as(as = "serde_with::base64::Base64")]
+    pub signed_blob: Vec<u8>,
+}
@@ -1,6 +1,46 @@
+pub mod bulk_restore_objects;
+pub mod cancel_operation;
+pub mod compose_object;
+pub mod create_hmac_key;
+pub mod delete_bucket_access_control;
+pub mod delete_default_object_access_control;
+pub mod delete_hmac_key;
+pub mod delete_notification;
+pub mod delete_object_access_control;
+pub mod get_bucket;
+pub mod get_bucket_access_control;
+pub mod get_bucket_iam_policy;
+pub mod get_default_object_access_control;
+pub mod get_hmac_key;
+pub mod get_notification;
+pub mod get_object;
+pub mod get_object_access_control;
+pub mod get_operation;
+pub mod insert_bucket_access_control;
+pub mod insert_default_object_access_control;
+pub mod insert_notification;
+pub mod insert_object;
+pub mod insert_object_access_control;
+pub mod list_bucket_access_controls;
+pub mod list_default_object_access_controls;
+pub mod list_hmac_keys;
+pub mod list_notifications;
+pub mod list_object_access_controls;
+pub mod list_objects;
+pub mod list_operations;
+pub mod patch_bucket;
+pub mod patch_bucket_access_control;
+pub mod patch_default_object_access_control;
 pub mod patch_object;
+pub mod patch_object_access_control;
+pub mod restore_object;
+pub mod rewrite_object;
+pub mod set_bucket_iam_policy;
+pub mod test_bucket_iam_permissions;
+pub mod update_hmac_key;
 
-use futures::future::{Either, MapErr};
+use super::query;
+use futures::future::{Either, MapErr, MapOk};
 use futures::{FutureExt, TryFutureExt};
 use http::{Request, Response};
 use http_body::Body;
@@ -11,24 +51,90 @@ use std::future::{self, Ready};
 use tower::util::Oneshot;
 use tower::{Service, ServiceBuilder, ServiceExt};
 
+fn bucket_uri<B>(bucket_name: B) -> String
+where
+    B: fmt::Display,
+{
+    format!("https://storage.googleapis.com/storage/v1/b/{bucket_name}")
+}
+
+fn project_uri<P>(project_id: P) -> String
+where
+    P: fmt::Display,
+{
+    format!("https://storage.googleapis.com/storage/v1/projects/{project_id}")
+}
+
+fn upload_uri<B>(bucket_name: B) -> String
+where
+    B: fmt::Display,
+{
+    format!("https://storage.googleapis.com/upload/storage/v1/b/{bucket_name}/o")
+}
+
 fn uri<B, O>(bucket_name: B, object_name: O) -> String
 where
     B: fmt::Display,
     O: AsRef<[u8]>,
 {
     format!(
-        "https://storage.googleapis.com/storage/v1/b/{bucket_name}/o/{}",
+        "{}/o/{}",
+        bucket_uri(bucket_name),
         percent_encoding::percent_encode(object_name.as_ref(), percent_encoding::NON_ALPHANUMERIC),
     )
 }
 
-fn send<S, T, U, V, W>(service: S, builder: http::request::Builder, body: V) -> Send<S, T, U, W>
+pub(super) fn send<S, T, U, V, W>(
+    service: S,
+    builder: http::request::Builder,
+    body: V,
+) -> Send<S, T, U, W>
 where
     S: Service<Request<T>, Response = Response<U>>,
     T: From<String>,
     U: Body,
     V: Serialize,
     W: for<'de> Deserialize<'de>,
+{
+    match builder.body(body) {
+        Ok(request) => match to_json::request(request) {
+            Ok(request) => call(service, request).left_future(),
+            Err(e) => future::ready(Err(Error::<S, T, U>::Json(e))).right_future(),
+        },
+        Err(e) => future::ready(Err(Error::<S, T, U>::Http(e))).right_future(),
+    }
+}
+
+pub(super) fn send_empty<S, T, U, W>(
+    service: S,
+    builder: http::request::Builder,
+) -> Send<S, T, U, W>
+where
+    S: Service<Request<T>, Response = Response<U>>,
+    T: From<String>,
+    U: Body,
+    W: for<'de> Deserialize<'de>,
+{
+    send_body(service, builder, T::from(String::new()))
+}
+
+fn send_body<S, T, U, W>(service: S, builder: http::request::Builder, body: T) -> Send<S, T, U, W>
+where
+    S: Service<Request<T>, Response = Response<U>>,
+    U: Body,
+    W: for<'de> Deserialize<'de>,
+{
+    match builder.body(body) {
+        Ok(request) => call(service, request).left_future(),
+        Err(e) => future::ready(Err(Error::<S, T, U>::Http(e))).right_future(),
+    }
+}
+
+fn call<S, T, U, W>(service: S, request: Request<T>) -> Call<S, T, U, W>
+where
+    S: Service<Request<T>, Response = Response<U>>,
+    U: Body,
+    W: for<'de> Deserialize<'de>,
 {
     let map_err: MapErrFn<S, T, U> = |value| match value {
         from_json::response::Error::Service(check_status::Error::Service(e)) => {
@@ -41,31 +147,58 @@ where
         }
         from_json::response::Error::Json(e) => Error::<S, T, U>::Json(e),
     };
-    match builder.body(body) {
-        Ok(request) => match to_json::request(request) {
-            Ok(request) => ServiceBuilder::new()
-                .layer(from_json::response::Layer::default())
-                .layer(check_status::Layer::default())
-                .service(service)
-                .oneshot(request)
-                .map_err(map_err)
-                .left_future(),
-            Err(e) => future::ready(Err(Error::<S, T, U>::Json(e))).right_future(),
-        },
-        Err(e) => future::ready(Err(Error::<S, T, U>::Http(e))).right_future(),
-    }
+    ServiceBuilder::new()
+        .layer(from_json::response::Layer::default())
+        .layer(check_status::Layer::default())
+        .service(service)
+        .oneshot(request)
+        .map_err(map_err)
 }
-type Send<S, T, U, W> = Either<
-    MapErr<
-        Oneshot<from_json::response::Service<check_status::Service<S>, W>, Request<T>>,
-        MapErrFn<S, T, U>,
-    >,
-    Ready<Result<Response<W>, Error<S, T, U>>>,
+type Call<S, T, U, W> = MapErr<
+    Oneshot<from_json::response::Service<check_status::Service<S>, W>, Request<T>>,
+    MapErrFn<S, T, U>,
 >;
+pub(super) type Send<S, T, U, W> =
+    Either<Call<S, T, U, W>, Ready<Result<Response<W>, Error<S, T, U>>>>;
 type MapErrFn<S, T, U> = fn(
     from_json::response::Error<
         check_status::Error<<S as Service<Request<T>>>::Error, <U as Body>::Error>,
         <U as Body>::Error,
     >,
 ) -> Error<S, T, U>;
+
+fn send_no_content<S, T, U>(service: S, builder: http::request::Builder) -> SendNoContent<S, T, U>
+where
+    S: Service<Request<T>, Response = Response<U>>,
+    T: From<String>,
+    U: Body,
+{
+    let map_err: NoContentMapErrFn<S, T, U> = |value| match value {
+        check_status::Error::Body(e) => Error::<S, T, U>::Body(e),
+        check_status::Error::Service(e) => Error::<S, T, U>::Service(e),
+        check_status::Error::Status(e) => Error::<S, T, U>::Status(e),
+    };
+    let map_ok: fn(Response<U>) -> Response<()> = |response| response.map(|_| ());
+    match builder.body(T::from(String::new())) {
+        Ok(request) => ServiceBuilder::new()
+            .layer(check_status::Layer::default())
+            .service(service)
+            .oneshot(request)
+            .map_err(map_err)
+            .map_ok(map_ok)
+            .left_future(),
+        Err(e) => future::ready(Err(Error::<S, T, U>::Http(e))).right_future(),
+    }
+}
+type SendNoContent<S, T, U> = Either<
+    MapOk<
+        MapErr<Oneshot<check_status::Service<S>, Request<T>>, NoContentMapErrFn<S, T, U>>,
+        fn(Response<U>) -> Response<()>,
+    >,
+    Ready<Result<Response<()>, Error<S, T, U>>>,
+>;
+type NoContentMapErrFn<S, T, U> = fn(
+    check_status::Error<<S as Service<Request<T>>>::Error, <U as Body>::Error>,
+) -> Error<S, T, U>;
+
 type Error<S, T, U> = super::Error<<S as Service<Request<T>>>::Error, <U as Body>::Error>;
@@ -0,0 +1,67 @@
+// https://cloud.google.com/storage/docs/json_api/v1/objects/bulkRestore
+
+use time::OffsetDateTime;
+
+pub fn builder<B>(bucket_name: B, request: Request) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        request,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    request: Request,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            request,
+        } = self;
+        let builder =
+            http::Request::post(format!("{}/o/bulkRestore", super::bucket_uri(bucket_name)));
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+#[derive(Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub allow_overwrite: bool,
+    pub copy_source_acl: bool,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    pub created_after_time: Option<OffsetDateTime>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    pub created_before_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub match_globs: Vec<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    pub soft_deleted_after_time: Option<OffsetDateTime>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    pub soft_deleted_before_time: Option<OffsetDateTime>,
+}
+
+pub type Response = crate::operation::Operation;
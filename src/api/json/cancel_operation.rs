@@ -0,0 +1,37 @@
+// https://cloud.google.com/storage/docs/json_api/v1/operations/cancel
+
+pub fn builder<B, O>(bucket_name: B, operation_id: O) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        operation_id: operation_id.into(),
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    operation_id: String,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            operation_id,
+        } = self;
+        let builder = http::Request::post(format!(
+            "{}/operations/{operation_id}/cancel",
+            super::bucket_uri(bucket_name),
+        ));
+        super::send_no_content(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::SendNoContent<S, T, U>;
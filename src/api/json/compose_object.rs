@@ -0,0 +1,142 @@
+// https://cloud.google.com/storage/docs/json_api/v1/objects/compose
+
+use crate::encryption::CustomerEncryptionKey;
+
+pub fn builder<B, O>(bucket_name: B, object_name: O, source_objects: Vec<SourceObject>) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        object_name: object_name.into(),
+        request: Request {
+            destination: Destination::default(),
+            source_objects,
+        },
+        customer_encryption_key: None,
+        if_generation_match: None,
+        kms_key_name: None,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    object_name: String,
+    request: Request,
+    customer_encryption_key: Option<CustomerEncryptionKey>,
+    if_generation_match: Option<u64>,
+    kms_key_name: Option<String>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            object_name,
+            request,
+            customer_encryption_key,
+            if_generation_match,
+            kms_key_name,
+        } = self;
+        let uri = super::query(
+            format!("{}/compose", super::uri(bucket_name, object_name)),
+            [
+                if_generation_match.map(|value| ("ifGenerationMatch", value.to_string())),
+                kms_key_name.map(|kms_key_name| ("kmsKeyName", kms_key_name)),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        let builder = crate::encryption::with_headers(
+            http::Request::post(uri),
+            customer_encryption_key
+                .iter()
+                .flat_map(CustomerEncryptionKey::headers),
+        );
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    pub fn content_type(mut self, content_type: mime::Mime) -> Self {
+        self.request.destination.content_type = Some(content_type);
+        self
+    }
+
+    // all source objects must be encrypted with this key
+    pub fn customer_encryption_key(mut self, key: CustomerEncryptionKey) -> Self {
+        self.customer_encryption_key = Some(key);
+        self
+    }
+
+    pub fn if_generation_match(mut self, if_generation_match: u64) -> Self {
+        self.if_generation_match = Some(if_generation_match);
+        self
+    }
+
+    pub fn kms_key_name<K>(mut self, kms_key_name: K) -> Self
+    where
+        K: Into<String>,
+    {
+        self.kms_key_name = Some(kms_key_name.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub destination: Destination,
+    pub source_objects: Vec<SourceObject>,
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Destination {
+    #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<mime::Mime>,
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceObject {
+    pub name: String,
+    #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_preconditions: Option<ObjectPreconditions>,
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectPreconditions {
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub if_generation_match: u64,
+}
+
+impl SourceObject {
+    pub fn new<N>(name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            generation: None,
+            object_preconditions: None,
+        }
+    }
+}
+
+pub type Response = crate::object::Object;
@@ -0,0 +1,40 @@
+// https://cloud.google.com/storage/docs/json_api/v1/projects/hmacKeys/create
+
+pub fn builder<P, E>(project_id: P, service_account_email: E) -> Builder
+where
+    P: Into<String>,
+    E: Into<String>,
+{
+    Builder {
+        project_id: project_id.into(),
+        service_account_email: service_account_email.into(),
+    }
+}
+
+pub struct Builder {
+    project_id: String,
+    service_account_email: String,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            project_id,
+            service_account_email,
+        } = self;
+        let uri = super::query(
+            format!("{}/hmacKeys", super::project_uri(project_id)),
+            [("serviceAccountEmail", service_account_email)],
+        );
+        let builder = http::Request::post(uri);
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+pub type Response = crate::hmac_key::HmacKey;
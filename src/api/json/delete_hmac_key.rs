@@ -0,0 +1,37 @@
+// https://cloud.google.com/storage/docs/json_api/v1/projects/hmacKeys/delete
+
+pub fn builder<P, A>(project_id: P, access_id: A) -> Builder
+where
+    P: Into<String>,
+    A: Into<String>,
+{
+    Builder {
+        project_id: project_id.into(),
+        access_id: access_id.into(),
+    }
+}
+
+pub struct Builder {
+    project_id: String,
+    access_id: String,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            project_id,
+            access_id,
+        } = self;
+        let builder = http::Request::delete(format!(
+            "{}/hmacKeys/{access_id}",
+            super::project_uri(project_id),
+        ));
+        super::send_no_content(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::SendNoContent<S, T, U>;
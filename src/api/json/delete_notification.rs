@@ -0,0 +1,37 @@
+// https://cloud.google.com/storage/docs/json_api/v1/notifications/delete
+
+pub fn builder<B, N>(bucket_name: B, notification: N) -> Builder
+where
+    B: Into<String>,
+    N: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        notification: notification.into(),
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    notification: String,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            notification,
+        } = self;
+        let builder = http::Request::delete(format!(
+            "{}/notificationConfigs/{notification}",
+            super::bucket_uri(bucket_name),
+        ));
+        super::send_no_content(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::SendNoContent<S, T, U>;
@@ -0,0 +1,44 @@
+// https://cloud.google.com/storage/docs/json_api/v1/bucketAccessControls/get
+
+use crate::acl::Entity;
+
+pub fn builder<B>(bucket_name: B, entity: Entity) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        entity,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    entity: Entity,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            entity,
+        } = self;
+        let builder = http::Request::get(format!(
+            "{}/acl/{}",
+            super::bucket_uri(bucket_name),
+            percent_encoding::utf8_percent_encode(
+                &entity.to_string(),
+                percent_encoding::NON_ALPHANUMERIC,
+            ),
+        ));
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+pub type Response = crate::acl::AccessControl;
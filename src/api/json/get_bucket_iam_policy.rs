@@ -0,0 +1,55 @@
+// https://cloud.google.com/storage/docs/json_api/v1/buckets/getIamPolicy
+
+pub fn builder<B>(bucket_name: B) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        options_requested_policy_version: None,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    options_requested_policy_version: Option<u32>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            options_requested_policy_version,
+        } = self;
+        let uri = super::query(
+            format!("{}/iam", super::bucket_uri(bucket_name)),
+            options_requested_policy_version.map(|options_requested_policy_version| {
+                (
+                    "optionsRequestedPolicyVersion",
+                    options_requested_policy_version.to_string(),
+                )
+            }),
+        );
+        let builder = http::Request::get(uri);
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    // 3 is required to read conditional bindings
+    pub fn options_requested_policy_version(
+        mut self,
+        options_requested_policy_version: u32,
+    ) -> Self {
+        self.options_requested_policy_version = Some(options_requested_policy_version);
+        self
+    }
+}
+
+pub type Response = crate::iam::Policy;
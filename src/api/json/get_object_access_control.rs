@@ -0,0 +1,62 @@
+// https://cloud.google.com/storage/docs/json_api/v1/objectAccessControls/get
+
+use crate::acl::Entity;
+
+pub fn builder<B, O>(bucket_name: B, object_name: O, entity: Entity) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        object_name: object_name.into(),
+        entity,
+        generation: None,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    object_name: String,
+    entity: Entity,
+    generation: Option<u64>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            object_name,
+            entity,
+            generation,
+        } = self;
+        let uri = super::query(
+            format!(
+                "{}/acl/{}",
+                super::uri(bucket_name, object_name),
+                percent_encoding::utf8_percent_encode(
+                    &entity.to_string(),
+                    percent_encoding::NON_ALPHANUMERIC,
+                ),
+            ),
+            generation.map(|generation| ("generation", generation.to_string())),
+        );
+        let builder = http::Request::get(uri);
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    pub fn generation(mut self, generation: u64) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+}
+
+pub type Response = crate::acl::AccessControl;
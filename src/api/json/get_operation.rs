@@ -0,0 +1,203 @@
+// https://cloud.google.com/storage/docs/json_api/v1/operations/get
+
+use crate::retry::Delays;
+use http::Request;
+use std::future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+pub fn builder<B, O>(bucket_name: B, operation_id: O) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        operation_id: operation_id.into(),
+        backoff: Backoff::default(),
+    }
+}
+
+#[derive(Clone)]
+pub struct Builder {
+    bucket_name: String,
+    operation_id: String,
+    backoff: Backoff,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            operation_id,
+            ..
+        } = self;
+        let builder = http::Request::get(format!(
+            "{}/operations/{operation_id}",
+            super::bucket_uri(bucket_name),
+        ));
+        super::send_empty(service, builder)
+    }
+
+    // e.g. `.poll_until_done(service, tokio::time::sleep)`. an operation that is done with an
+    // error fails with Error::Operation.
+    pub fn poll_until_done<S, T, U, F, Fut>(
+        self,
+        service: S,
+        sleep: F,
+    ) -> PollUntilDone<S, T, U, F, Fut>
+    where
+        S: Clone + tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+        F: FnMut(Duration) -> Fut,
+    {
+        let delays = self.backoff.delays();
+        let f = Box::pin(self.clone().send(service.clone()));
+        PollUntilDone {
+            builder: self,
+            service,
+            sleep,
+            delays,
+            state: State::S0 { f },
+        }
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+pub type Response = crate::operation::Operation;
+
+pub use crate::retry::Backoff;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<S, B> {
+    #[error(transparent)]
+    Api(crate::api::Error<S, B>),
+    // the operation is done but failed
+    #[error("operation failed with code {}: {}", .0.code, .0.message)]
+    Operation(crate::operation::Status),
+}
+
+#[pin_project::pin_project]
+pub struct PollUntilDone<S, T, U, F, Fut>
+where
+    S: tower::Service<Request<T>, Response = http::Response<U>>,
+    U: http_body::Body,
+{
+    builder: Builder,
+    service: S,
+    sleep: F,
+    delays: Delays,
+    #[pin]
+    state: State<S, T, U, Fut>,
+}
+
+#[pin_project::pin_project(project = StateProj)]
+enum State<S, T, U, Fut>
+where
+    S: tower::Service<Request<T>, Response = http::Response<U>>,
+    U: http_body::Body,
+{
+    S0 {
+        f: Pin<Box<Future<S, T, U>>>,
+    },
+    S1 {
+        #[pin]
+        f: Fut,
+    },
+}
+
+impl<S, T, U, F, Fut> future::Future for PollUntilDone<S, T, U, F, Fut>
+where
+    S: Clone + tower::Service<http::Request<T>, Response = http::Response<U>>,
+    T: From<String>,
+    U: http_body::Body,
+    F: FnMut(Duration) -> Fut,
+    Fut: future::Future<Output = ()>,
+{
+    type Output = Result<http::Response<Response>, Error<S::Error, U::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            match this.state.as_mut().project() {
+                StateProj::S0 { f } => {
+                    let mut response = ready!(f.as_mut().poll(cx)).map_err(Error::Api)?;
+                    let operation = response.body_mut();
+                    if operation.done {
+                        break Poll::Ready(match operation.error.take() {
+                            Some(status) => Err(Error::Operation(status)),
+                            None => Ok(response),
+                        });
+                    }
+                    let f = (this.sleep)(this.delays.next().unwrap_or_default());
+                    this.state.set(State::S1 { f });
+                }
+                StateProj::S1 { f } => {
+                    ready!(f.poll(cx));
+                    let f = Box::pin(this.builder.clone().send(this.service.clone()));
+                    this.state.set(State::S0 { f });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::{Expectation, Service};
+    use http::Method;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_poll_until_done() {
+        let service = Service::new();
+        let uri = "https://storage.googleapis.com/storage/v1/b/bucket/operations/op";
+        service.expect(
+            Expectation::new(Method::GET, uri).response_json(&serde_json::json!({
+                "name": "projects/_/buckets/bucket/operations/op",
+            })),
+        );
+        service.expect(
+            Expectation::new(Method::GET, uri).response_json(&serde_json::json!({
+                "done": true,
+                "error": {"code": 7, "message": "denied"},
+                "name": "projects/_/buckets/bucket/operations/op",
+            })),
+        );
+
+        let mut delays = Vec::new();
+        let e = super::builder("bucket", "op")
+            .backoff(super::Backoff {
+                initial: Duration::ZERO,
+                max: Duration::ZERO,
+                multiplier: 0,
+            })
+            .poll_until_done::<_, String, _, _, _>(service.clone(), |delay| {
+                delays.push(delay);
+                std::future::ready(())
+            })
+            .await
+            .unwrap_err();
+        if let super::Error::Operation(status) = e {
+            assert_eq!((status.code, status.message.as_str()), (7, "denied"));
+        } else {
+            panic!("unexpected error");
+        }
+        assert_eq!(delays, [Duration::from_millis(100)]);
+        service.assert_done();
+    }
+}
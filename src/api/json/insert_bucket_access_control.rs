@@ -0,0 +1,43 @@
+// https://cloud.google.com/storage/docs/json_api/v1/bucketAccessControls/insert
+
+pub fn builder<B>(bucket_name: B, request: Request) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        request,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    request: Request,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            request,
+        } = self;
+        let builder = http::Request::post(format!("{}/acl", super::bucket_uri(bucket_name)));
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+#[serde_with::serde_as]
+#[derive(serde::Serialize)]
+pub struct Request {
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub entity: crate::acl::Entity,
+    pub role: crate::acl::Role,
+}
+
+pub type Response = crate::acl::AccessControl;
@@ -0,0 +1,40 @@
+// https://cloud.google.com/storage/docs/json_api/v1/notifications/insert
+
+pub fn builder<B>(bucket_name: B, request: Request) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        request,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    request: Request,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            request,
+        } = self;
+        let builder = http::Request::post(format!(
+            "{}/notificationConfigs",
+            super::bucket_uri(bucket_name),
+        ));
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+pub type Request = crate::notification::Notification;
+
+pub type Response = crate::notification::Notification;
@@ -0,0 +1,88 @@
+// https://cloud.google.com/storage/docs/json_api/v1/objects/insert
+
+use crate::encryption::CustomerEncryptionKey;
+
+pub fn builder<B, O, T>(bucket_name: B, object_name: O, body: T) -> Builder<T>
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        object_name: object_name.into(),
+        body,
+        content_type: None,
+        customer_encryption_key: None,
+        kms_key_name: None,
+    }
+}
+
+pub struct Builder<T> {
+    bucket_name: String,
+    object_name: String,
+    body: T,
+    content_type: Option<mime::Mime>,
+    customer_encryption_key: Option<CustomerEncryptionKey>,
+    kms_key_name: Option<String>,
+}
+
+impl<T> Builder<T> {
+    pub fn send<S, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            object_name,
+            body,
+            content_type,
+            customer_encryption_key,
+            kms_key_name,
+        } = self;
+        let uri = super::query(
+            super::upload_uri(bucket_name),
+            [
+                Some(("uploadType", "media".to_owned())),
+                Some(("name", object_name)),
+                kms_key_name.map(|kms_key_name| ("kmsKeyName", kms_key_name)),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        let mut builder = http::Request::post(uri);
+        if let Some(content_type) = content_type {
+            builder = builder.header(http::header::CONTENT_TYPE, content_type.as_ref());
+        }
+        let builder = crate::encryption::with_headers(
+            builder,
+            customer_encryption_key
+                .iter()
+                .flat_map(CustomerEncryptionKey::headers),
+        );
+        super::send_body(service, builder, body)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl<T> Builder<T> {
+    pub fn content_type(mut self, content_type: mime::Mime) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    pub fn customer_encryption_key(mut self, key: CustomerEncryptionKey) -> Self {
+        self.customer_encryption_key = Some(key);
+        self
+    }
+
+    pub fn kms_key_name<K>(mut self, kms_key_name: K) -> Self
+    where
+        K: Into<String>,
+    {
+        self.kms_key_name = Some(kms_key_name.into());
+        self
+    }
+}
+
+pub type Response = crate::object::Object;
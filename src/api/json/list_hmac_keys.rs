@@ -0,0 +1,91 @@
+// https://cloud.google.com/storage/docs/json_api/v1/projects/hmacKeys/list
+
+pub fn builder<P>(project_id: P) -> Builder
+where
+    P: Into<String>,
+{
+    Builder {
+        project_id: project_id.into(),
+        max_results: None,
+        page_token: None,
+        service_account_email: None,
+        show_deleted_keys: None,
+    }
+}
+
+pub struct Builder {
+    project_id: String,
+    max_results: Option<u32>,
+    page_token: Option<String>,
+    service_account_email: Option<String>,
+    show_deleted_keys: Option<bool>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            project_id,
+            max_results,
+            page_token,
+            service_account_email,
+            show_deleted_keys,
+        } = self;
+        let uri = super::query(
+            format!("{}/hmacKeys", super::project_uri(project_id)),
+            [
+                max_results.map(|max_results| ("maxResults", max_results.to_string())),
+                page_token.map(|page_token| ("pageToken", page_token)),
+                service_account_email
+                    .map(|service_account_email| ("serviceAccountEmail", service_account_email)),
+                show_deleted_keys
+                    .map(|show_deleted_keys| ("showDeletedKeys", show_deleted_keys.to_string())),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        let builder = http::Request::get(uri);
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    pub fn max_results(mut self, max_results: u32) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    pub fn page_token<P>(mut self, page_token: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.page_token = Some(page_token.into());
+        self
+    }
+
+    pub fn service_account_email<E>(mut self, service_account_email: E) -> Self
+    where
+        E: Into<String>,
+    {
+        self.service_account_email = Some(service_account_email.into());
+        self
+    }
+
+    pub fn show_deleted_keys(mut self, show_deleted_keys: bool) -> Self {
+        self.show_deleted_keys = Some(show_deleted_keys);
+        self
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    #[serde(default)]
+    pub items: Vec<crate::hmac_key::HmacKeyMetadata>,
+    pub next_page_token: Option<String>,
+}
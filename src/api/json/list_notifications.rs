@@ -0,0 +1,37 @@
+// https://cloud.google.com/storage/docs/json_api/v1/notifications/list
+
+pub fn builder<B>(bucket_name: B) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self { bucket_name } = self;
+        let builder = http::Request::get(format!(
+            "{}/notificationConfigs",
+            super::bucket_uri(bucket_name),
+        ));
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    pub items: Vec<crate::notification::Notification>,
+}
@@ -0,0 +1,112 @@
+// https://cloud.google.com/storage/docs/json_api/v1/objects/list
+
+pub fn builder<B>(bucket_name: B) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        delimiter: None,
+        max_results: None,
+        page_token: None,
+        prefix: None,
+        soft_deleted: false,
+        versions: false,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    delimiter: Option<String>,
+    max_results: Option<u32>,
+    page_token: Option<String>,
+    prefix: Option<String>,
+    soft_deleted: bool,
+    versions: bool,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            delimiter,
+            max_results,
+            page_token,
+            prefix,
+            soft_deleted,
+            versions,
+        } = self;
+        let uri = super::query(
+            format!("{}/o", super::bucket_uri(bucket_name)),
+            [
+                delimiter.map(|delimiter| ("delimiter", delimiter)),
+                max_results.map(|max_results| ("maxResults", max_results.to_string())),
+                page_token.map(|page_token| ("pageToken", page_token)),
+                prefix.map(|prefix| ("prefix", prefix)),
+                soft_deleted.then(|| ("softDeleted", "true".to_owned())),
+                versions.then(|| ("versions", "true".to_owned())),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        let builder = http::Request::get(uri);
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    pub fn delimiter<D>(mut self, delimiter: D) -> Self
+    where
+        D: Into<String>,
+    {
+        self.delimiter = Some(delimiter.into());
+        self
+    }
+
+    pub fn max_results(mut self, max_results: u32) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    pub fn page_token<P>(mut self, page_token: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.page_token = Some(page_token.into());
+        self
+    }
+
+    pub fn prefix<P>(mut self, prefix: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn soft_deleted(mut self, soft_deleted: bool) -> Self {
+        self.soft_deleted = soft_deleted;
+        self
+    }
+
+    pub fn versions(mut self, versions: bool) -> Self {
+        self.versions = versions;
+        self
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    #[serde(default)]
+    pub items: Vec<crate::object::Object>,
+    pub next_page_token: Option<String>,
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+}
@@ -0,0 +1,80 @@
+// https://cloud.google.com/storage/docs/json_api/v1/operations/list
+
+pub fn builder<B>(bucket_name: B) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        filter: None,
+        page_size: None,
+        page_token: None,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    filter: Option<String>,
+    page_size: Option<u32>,
+    page_token: Option<String>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            filter,
+            page_size,
+            page_token,
+        } = self;
+        let uri = super::query(
+            format!("{}/operations", super::bucket_uri(bucket_name)),
+            [
+                filter.map(|filter| ("filter", filter)),
+                page_size.map(|page_size| ("pageSize", page_size.to_string())),
+                page_token.map(|page_token| ("pageToken", page_token)),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        let builder = http::Request::get(uri);
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Into<String>,
+    {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    pub fn page_token<P>(mut self, page_token: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.page_token = Some(page_token.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub next_page_token: Option<String>,
+    #[serde(default)]
+    pub operations: Vec<crate::operation::Operation>,
+}
@@ -0,0 +1,92 @@
+// https://cloud.google.com/storage/docs/json_api/v1/buckets/patch
+
+use crate::acl::PredefinedAcl;
+
+pub fn builder<B>(bucket_name: B, request: Request) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        request,
+        predefined_acl: None,
+        predefined_default_object_acl: None,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    request: Request,
+    predefined_acl: Option<PredefinedAcl>,
+    predefined_default_object_acl: Option<PredefinedAcl>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            request,
+            predefined_acl,
+            predefined_default_object_acl,
+        } = self;
+        let uri = super::query(
+            super::bucket_uri(bucket_name),
+            [
+                predefined_acl.map(|predefined_acl| {
+                    ("predefinedAcl", predefined_acl.as_json_str().to_owned())
+                }),
+                predefined_default_object_acl.map(|predefined_default_object_acl| {
+                    (
+                        "predefinedDefaultObjectAcl",
+                        predefined_default_object_acl.as_json_str().to_owned(),
+                    )
+                }),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        let builder = http::Request::patch(uri);
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    pub fn predefined_acl(mut self, predefined_acl: PredefinedAcl) -> Self {
+        self.predefined_acl = Some(predefined_acl);
+        self
+    }
+
+    pub fn predefined_default_object_acl(
+        mut self,
+        predefined_default_object_acl: PredefinedAcl,
+    ) -> Self {
+        self.predefined_default_object_acl = Some(predefined_default_object_acl);
+        self
+    }
+}
+
+#[derive(Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cors: Option<Vec<crate::bucket::Cors>>,
+    // Encryption { default_kms_key_name: None } removes the default key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<crate::bucket::Encryption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifecycle: Option<crate::bucket::Lifecycle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soft_delete_policy: Option<crate::bucket::SoftDeletePolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub versioning: Option<crate::bucket::Versioning>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<crate::bucket::Website>,
+}
+
+pub type Response = crate::bucket::Bucket;
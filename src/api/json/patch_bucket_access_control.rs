@@ -0,0 +1,53 @@
+// https://cloud.google.com/storage/docs/json_api/v1/bucketAccessControls/patch
+
+use crate::acl::Entity;
+
+pub fn builder<B>(bucket_name: B, entity: Entity, request: Request) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        entity,
+        request,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    entity: Entity,
+    request: Request,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            entity,
+            request,
+        } = self;
+        let builder = http::Request::patch(format!(
+            "{}/acl/{}",
+            super::bucket_uri(bucket_name),
+            percent_encoding::utf8_percent_encode(
+                &entity.to_string(),
+                percent_encoding::NON_ALPHANUMERIC,
+            ),
+        ));
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+#[derive(Default, serde::Serialize)]
+pub struct Request {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<crate::acl::Role>,
+}
+
+pub type Response = crate::acl::AccessControl;
@@ -1,5 +1,7 @@
 // https://cloud.google.com/storage/docs/json_api/v1/objects/patch
 
+use crate::acl::PredefinedAcl;
+
 pub fn builder<B, O>(bucket_name: B, object_name: O, request: Request) -> Builder
 where
     B: Into<String>,
@@ -9,6 +11,7 @@ where
         bucket_name: bucket_name.into(),
         object_name: object_name.into(),
         request,
+        predefined_acl: None,
     }
 }
 
@@ -16,6 +19,7 @@ pub struct Builder {
     bucket_name: String,
     object_name: String,
     request: Request,
+    predefined_acl: Option<PredefinedAcl>,
 }
 
 impl Builder {
@@ -29,13 +33,26 @@ impl Builder {
             bucket_name,
             object_name,
             request,
+            predefined_acl,
         } = self;
-        let builder = http::Request::patch(super::uri(bucket_name, object_name));
+        let uri = super::query(
+            super::uri(bucket_name, object_name),
+            predefined_acl
+                .map(|predefined_acl| ("predefinedAcl", predefined_acl.as_json_str().to_owned())),
+        );
+        let builder = http::Request::patch(uri);
         super::send(service, builder, request)
     }
 }
 pub type Future<S, T, U> = super::Send<S, T, U, Response>;
 
+impl Builder {
+    pub fn predefined_acl(mut self, predefined_acl: PredefinedAcl) -> Self {
+        self.predefined_acl = Some(predefined_acl);
+        self
+    }
+}
+
 #[serde_with::serde_as]
 #[derive(Default, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -45,19 +62,4 @@ pub struct Request {
     pub content_type: Option<mime::Mime>,
 }
 
-#[serde_with::serde_as]
-#[derive(Clone, Debug, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Response {
-    pub bucket: String,
-    #[serde_as(as = "serde_with::DisplayFromStr")]
-    pub content_type: mime::Mime,
-    #[serde_as(as = "serde_with::base64::Base64")]
-    pub crc32c: [u8; 4],
-    pub id: String,
-    #[serde_as(as = "Option<serde_with::base64::Base64>")]
-    pub md5_hash: Option<[u8; 16]>,
-    pub name: String,
-    #[serde_as(as = "serde_with::DisplayFromStr")]
-    pub size: u64,
-}
+pub type Response = crate::object::Object;
@@ -0,0 +1,96 @@
+// https://cloud.google.com/storage/docs/json_api/v1/objects/restore
+
+pub fn builder<B, O>(bucket_name: B, object_name: O, generation: u64) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        object_name: object_name.into(),
+        generation,
+        copy_source_acl: false,
+        if_generation_match: None,
+        if_generation_not_match: None,
+        if_metageneration_match: None,
+        if_metageneration_not_match: None,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    object_name: String,
+    generation: u64,
+    copy_source_acl: bool,
+    if_generation_match: Option<u64>,
+    if_generation_not_match: Option<u64>,
+    if_metageneration_match: Option<u64>,
+    if_metageneration_not_match: Option<u64>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            object_name,
+            generation,
+            copy_source_acl,
+            if_generation_match,
+            if_generation_not_match,
+            if_metageneration_match,
+            if_metageneration_not_match,
+        } = self;
+        let uri = super::query(
+            format!("{}/restore", super::uri(bucket_name, object_name)),
+            [
+                Some(("generation", generation.to_string())),
+                copy_source_acl.then(|| ("copySourceAcl", "true".to_owned())),
+                if_generation_match.map(|value| ("ifGenerationMatch", value.to_string())),
+                if_generation_not_match.map(|value| ("ifGenerationNotMatch", value.to_string())),
+                if_metageneration_match.map(|value| ("ifMetagenerationMatch", value.to_string())),
+                if_metageneration_not_match
+                    .map(|value| ("ifMetagenerationNotMatch", value.to_string())),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        let builder = http::Request::post(uri);
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    pub fn copy_source_acl(mut self, copy_source_acl: bool) -> Self {
+        self.copy_source_acl = copy_source_acl;
+        self
+    }
+
+    // 0 requires that no live object exists
+    pub fn if_generation_match(mut self, if_generation_match: u64) -> Self {
+        self.if_generation_match = Some(if_generation_match);
+        self
+    }
+
+    pub fn if_generation_not_match(mut self, if_generation_not_match: u64) -> Self {
+        self.if_generation_not_match = Some(if_generation_not_match);
+        self
+    }
+
+    pub fn if_metageneration_match(mut self, if_metageneration_match: u64) -> Self {
+        self.if_metageneration_match = Some(if_metageneration_match);
+        self
+    }
+
+    pub fn if_metageneration_not_match(mut self, if_metageneration_not_match: u64) -> Self {
+        self.if_metageneration_not_match = Some(if_metageneration_not_match);
+        self
+    }
+}
+
+pub type Response = crate::object::Object;
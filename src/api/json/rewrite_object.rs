@@ -0,0 +1,232 @@
+// https://cloud.google.com/storage/docs/json_api/v1/objects/rewrite
+
+use crate::encryption::CustomerEncryptionKey;
+
+pub fn builder<SB, SO, DB, DO>(
+    source_bucket_name: SB,
+    source_object_name: SO,
+    destination_bucket_name: DB,
+    destination_object_name: DO,
+) -> Builder
+where
+    SB: Into<String>,
+    SO: Into<String>,
+    DB: Into<String>,
+    DO: Into<String>,
+{
+    Builder {
+        source_bucket_name: source_bucket_name.into(),
+        source_object_name: source_object_name.into(),
+        destination_bucket_name: destination_bucket_name.into(),
+        destination_object_name: destination_object_name.into(),
+        customer_encryption_key: None,
+        destination_kms_key_name: None,
+        max_bytes_rewritten_per_call: None,
+        rewrite_token: None,
+        source_customer_encryption_key: None,
+        source_generation: None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Builder {
+    source_bucket_name: String,
+    source_object_name: String,
+    destination_bucket_name: String,
+    destination_object_name: String,
+    customer_encryption_key: Option<CustomerEncryptionKey>,
+    destination_kms_key_name: Option<String>,
+    max_bytes_rewritten_per_call: Option<u64>,
+    rewrite_token: Option<String>,
+    source_customer_encryption_key: Option<CustomerEncryptionKey>,
+    source_generation: Option<u64>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            source_bucket_name,
+            source_object_name,
+            destination_bucket_name,
+            destination_object_name,
+            customer_encryption_key,
+            destination_kms_key_name,
+            max_bytes_rewritten_per_call,
+            rewrite_token,
+            source_customer_encryption_key,
+            source_generation,
+        } = self;
+        let uri = super::query(
+            format!(
+                "{}/rewriteTo/b/{destination_bucket_name}/o/{}",
+                super::uri(source_bucket_name, source_object_name),
+                percent_encoding::utf8_percent_encode(
+                    &destination_object_name,
+                    percent_encoding::NON_ALPHANUMERIC,
+                ),
+            ),
+            [
+                destination_kms_key_name.map(|destination_kms_key_name| {
+                    ("destinationKmsKeyName", destination_kms_key_name)
+                }),
+                max_bytes_rewritten_per_call
+                    .map(|value| ("maxBytesRewrittenPerCall", value.to_string())),
+                rewrite_token.map(|rewrite_token| ("rewriteToken", rewrite_token)),
+                source_generation.map(|value| ("sourceGeneration", value.to_string())),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        let builder = crate::encryption::with_headers(
+            http::Request::post(uri),
+            customer_encryption_key
+                .iter()
+                .flat_map(CustomerEncryptionKey::headers)
+                .chain(
+                    source_customer_encryption_key
+                        .iter()
+                        .flat_map(CustomerEncryptionKey::copy_source_headers),
+                ),
+        );
+        super::send(service, builder, serde_json::Map::new())
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    // the key for the destination object
+    pub fn customer_encryption_key(mut self, key: CustomerEncryptionKey) -> Self {
+        self.customer_encryption_key = Some(key);
+        self
+    }
+
+    pub fn destination_kms_key_name<K>(mut self, destination_kms_key_name: K) -> Self
+    where
+        K: Into<String>,
+    {
+        self.destination_kms_key_name = Some(destination_kms_key_name.into());
+        self
+    }
+
+    pub fn max_bytes_rewritten_per_call(mut self, max_bytes_rewritten_per_call: u64) -> Self {
+        self.max_bytes_rewritten_per_call = Some(max_bytes_rewritten_per_call);
+        self
+    }
+
+    // from the previous response, to continue a rewrite
+    pub fn rewrite_token<R>(mut self, rewrite_token: R) -> Self
+    where
+        R: Into<String>,
+    {
+        self.rewrite_token = Some(rewrite_token.into());
+        self
+    }
+
+    // required if the source object is encrypted with a customer-supplied key
+    pub fn source_customer_encryption_key(mut self, key: CustomerEncryptionKey) -> Self {
+        self.source_customer_encryption_key = Some(key);
+        self
+    }
+
+    pub fn source_generation(mut self, source_generation: u64) -> Self {
+        self.source_generation = Some(source_generation);
+        self
+    }
+}
+
+// sends the request repeatedly with the returned token until the rewrite completes,
+// e.g. to rotate the customer-supplied key of an object in place
+pub async fn send_until_done<S, T, U>(
+    mut builder: Builder,
+    service: S,
+) -> Result<http::Response<crate::object::Object>, Error<S::Error, U::Error>>
+where
+    S: Clone + tower::Service<http::Request<T>, Response = http::Response<U>>,
+    T: From<String>,
+    U: http_body::Body,
+{
+    loop {
+        let (parts, body) = builder
+            .clone()
+            .send(service.clone())
+            .await
+            .map_err(Error::Api)?
+            .into_parts();
+        if body.done {
+            let resource = body.resource.ok_or(Error::MissingResource)?;
+            break Ok(http::Response::from_parts(parts, resource));
+        }
+        builder.rewrite_token = Some(body.rewrite_token.ok_or(Error::MissingRewriteToken)?);
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<S, B> {
+    #[error(transparent)]
+    Api(crate::api::Error<S, B>),
+    #[error("rewrite is done but the response has no resource")]
+    MissingResource,
+    #[error("rewrite is not done but the response has no rewrite token")]
+    MissingRewriteToken,
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub done: bool,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub object_size: u64,
+    // set when done
+    pub resource: Option<crate::object::Object>,
+    pub rewrite_token: Option<String>,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub total_bytes_rewritten: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::{Expectation, Service};
+    use http::Method;
+
+    #[tokio::test]
+    async fn test_send_until_done_incomplete() {
+        let service = Service::new();
+        let uri = "https://storage.googleapis.com/storage/v1/b/a/o/x/rewriteTo/b/b/o/y";
+        service.expect(
+            Expectation::new(Method::POST, uri).response_json(&serde_json::json!({
+                "done": true,
+                "objectSize": "5",
+                "totalBytesRewritten": "5",
+            })),
+        );
+        service.expect(
+            Expectation::new(Method::POST, uri).response_json(&serde_json::json!({
+                "done": false,
+                "objectSize": "5",
+                "totalBytesRewritten": "0",
+            })),
+        );
+
+        let e = super::send_until_done::<_, String, _>(
+            super::builder("a", "x", "b", "y"),
+            service.clone(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(e, super::Error::MissingResource));
+        let e = super::send_until_done::<_, String, _>(
+            super::builder("a", "x", "b", "y"),
+            service.clone(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(e, super::Error::MissingRewriteToken));
+        service.assert_done();
+    }
+}
@@ -0,0 +1,156 @@
+// https://cloud.google.com/storage/docs/json_api/v1/buckets/setIamPolicy
+
+use crate::iam::Policy;
+use crate::retry::{self, Backoff};
+use http::StatusCode;
+use std::future;
+use std::time::Duration;
+
+pub fn builder<B>(bucket_name: B, request: Request) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        request,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    request: Request,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            request,
+        } = self;
+        let builder = http::Request::put(format!("{}/iam", super::bucket_uri(bucket_name)));
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+// the etag of the policy makes the update fail if the policy was modified in the meantime
+pub type Request = Policy;
+
+pub type Response = Policy;
+
+const MAX_ATTEMPTS: usize = 5;
+
+// gets the policy, applies `f` and sets it, retrying with a jittered backoff while concurrent
+// updates conflict, e.g. `read_modify_write(bucket_name, service, tokio::time::sleep, f)`. `f`
+// returns false to leave the policy as is. the last conflict is returned after 5 attempts.
+pub async fn read_modify_write<B, S, T, U, F, G, Fut>(
+    bucket_name: B,
+    service: S,
+    mut sleep: G,
+    mut f: F,
+) -> Result<http::Response<Policy>, super::Error<S, T, U>>
+where
+    B: Into<String>,
+    S: Clone + tower::Service<http::Request<T>, Response = http::Response<U>>,
+    T: From<String>,
+    U: http_body::Body,
+    F: FnMut(&mut Policy) -> bool,
+    G: FnMut(Duration) -> Fut,
+    Fut: future::Future<Output = ()>,
+{
+    let bucket_name = bucket_name.into();
+    let mut delays = Backoff {
+        initial: Duration::from_millis(200),
+        max: Duration::from_secs(5),
+        multiplier: 2,
+    }
+    .delays();
+    let mut attempt = 1;
+    loop {
+        let mut response = super::get_bucket_iam_policy::builder(&bucket_name)
+            .options_requested_policy_version(3)
+            .send(service.clone())
+            .await?;
+        let policy = response.body_mut();
+        if !f(policy) {
+            break Ok(response);
+        }
+        policy.version = 3;
+        match builder(&bucket_name, response.into_body())
+            .send(service.clone())
+            .await
+        {
+            Err(crate::api::Error::Status(crate::api::StatusError(e)))
+                if attempt < MAX_ATTEMPTS
+                    && (e.status() == StatusCode::CONFLICT
+                        || e.status() == StatusCode::PRECONDITION_FAILED) =>
+            {
+                sleep(retry::jitter(delays.next().unwrap_or_default())).await;
+                attempt += 1;
+            }
+            result => break result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::{Expectation, Service};
+    use http::{Method, StatusCode};
+
+    #[tokio::test]
+    async fn test_read_modify_write_conflict() {
+        let service = Service::new();
+        let uri = "https://storage.googleapis.com/storage/v1/b/bucket/iam";
+        for _ in 0..5 {
+            service.expect(
+                Expectation::new(
+                    Method::GET,
+                    format!("{uri}?optionsRequestedPolicyVersion=3"),
+                )
+                .response_json(&serde_json::json!({
+                    "bindings": [],
+                    "etag": "CAE=",
+                    "version": 1,
+                })),
+            );
+            service.expect(
+                Expectation::new(Method::PUT, uri)
+                    .status(StatusCode::PRECONDITION_FAILED)
+                    .response_body("conflict"),
+            );
+        }
+
+        let mut delays = Vec::new();
+        let e = super::read_modify_write::<_, _, String, _, _, _, _>(
+            "bucket",
+            service.clone(),
+            |delay| {
+                delays.push(delay);
+                std::future::ready(())
+            },
+            |policy| {
+                policy.bindings.push(crate::iam::Binding {
+                    condition: None,
+                    members: vec!["user:a@example.com".to_owned()],
+                    role: "roles/storage.objectViewer".to_owned(),
+                });
+                true
+            },
+        )
+        .await
+        .unwrap_err();
+        if let crate::api::Error::Status(crate::api::StatusError(e)) = e {
+            assert_eq!(e.status(), StatusCode::PRECONDITION_FAILED);
+        } else {
+            panic!("unexpected error");
+        }
+        assert_eq!(delays.len(), 4);
+        service.assert_done();
+    }
+}
@@ -0,0 +1,48 @@
+// https://cloud.google.com/storage/docs/json_api/v1/buckets/testIamPermissions
+
+pub fn builder<B, I>(bucket_name: B, permissions: I) -> Builder
+where
+    B: Into<String>,
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        permissions: permissions.into_iter().map(Into::into).collect(),
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    permissions: Vec<String>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            bucket_name,
+            permissions,
+        } = self;
+        let uri = super::query(
+            format!("{}/iam/testPermissions", super::bucket_uri(bucket_name)),
+            permissions
+                .into_iter()
+                .map(|permission| ("permissions", permission)),
+        );
+        let builder = http::Request::get(uri);
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct Response {
+    // the subset of the requested permissions held by the caller
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
@@ -0,0 +1,62 @@
+// https://cloud.google.com/storage/docs/json_api/v1/projects/hmacKeys/update
+
+use crate::hmac_key::State;
+
+pub fn builder<P, A>(project_id: P, access_id: A, state: State) -> Builder
+where
+    P: Into<String>,
+    A: Into<String>,
+{
+    Builder {
+        project_id: project_id.into(),
+        access_id: access_id.into(),
+        request: Request { etag: None, state },
+    }
+}
+
+pub struct Builder {
+    project_id: String,
+    access_id: String,
+    request: Request,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            project_id,
+            access_id,
+            request,
+        } = self;
+        let builder = http::Request::put(format!(
+            "{}/hmacKeys/{access_id}",
+            super::project_uri(project_id),
+        ));
+        super::send(service, builder, request)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    // fails with 412 if the key has been modified since the etag was read
+    pub fn etag<E>(mut self, etag: E) -> Self
+    where
+        E: Into<String>,
+    {
+        self.request.etag = Some(etag.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Request {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub state: State,
+}
+
+pub type Response = crate::hmac_key::HmacKeyMetadata;
@@ -0,0 +1,14 @@
+// https://cloud.google.com/compute/docs/metadata/querying-metadata
+
+pub mod get_token;
+
+use super::json::{send_empty, Send};
+use super::query;
+use std::env;
+
+const METADATA_FLAVOR: &str = "metadata-flavor";
+
+// the metadata server address, which GCE_METADATA_HOST overrides (e.g. for an emulator)
+fn host() -> String {
+    env::var("GCE_METADATA_HOST").unwrap_or_else(|_| "metadata.google.internal".to_owned())
+}
@@ -0,0 +1,73 @@
+// https://cloud.google.com/compute/docs/access/authenticate-workloads#applications
+
+pub fn builder() -> Builder {
+    Builder {
+        host: super::host(),
+        service_account: "default".to_owned(),
+        scopes: Vec::new(),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Builder {
+    host: String,
+    service_account: String,
+    scopes: Vec<String>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: tower::Service<http::Request<T>, Response = http::Response<U>>,
+        T: From<String>,
+        U: http_body::Body,
+    {
+        let Self {
+            host,
+            service_account,
+            scopes,
+        } = self;
+        let uri = super::query(
+            format!(
+                "http://{host}/computeMetadata/v1/instance/service-accounts/{service_account}/token"
+            ),
+            (!scopes.is_empty()).then(|| ("scopes", scopes.join(","))),
+        );
+        let builder = http::Request::get(uri).header(super::METADATA_FLAVOR, "Google");
+        super::send_empty(service, builder)
+    }
+}
+pub type Future<S, T, U> = super::Send<S, T, U, Response>;
+
+impl Builder {
+    // host[:port], defaults to GCE_METADATA_HOST or metadata.google.internal
+    pub fn host<H>(mut self, host: H) -> Self
+    where
+        H: Into<String>,
+    {
+        self.host = host.into();
+        self
+    }
+
+    // an email or "default"
+    pub fn service_account<A>(mut self, service_account: A) -> Self
+    where
+        A: Into<String>,
+    {
+        self.service_account = service_account.into();
+        self
+    }
+
+    // https://www.googleapis.com/auth/..., the scopes of the instance by default
+    pub fn scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct Response {
+    pub access_token: String,
+    pub expires_in: i64,
+    pub token_type: String,
+}
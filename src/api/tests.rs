@@ -5,15 +5,36 @@ use http_body_util::combinators::UnsyncBoxBody;
 use http_body_util::{BodyExt, Full};
 use hyper_rustls::ConfigBuilderExt;
 use md5::{Digest, Md5};
-use std::convert::Infallible;
+use std::convert::{self, Infallible};
 use std::env;
 use std::sync::Arc;
 use tower::{Layer, ServiceExt};
 
 type Connector = hyper_rustls::HttpsConnector<hyper_util::client::legacy::connect::HttpConnector>;
-type Client = hyper_util::client::legacy::Client<Connector, UnsyncBoxBody<Bytes, Infallible>>;
-type Service = crate::yup_oauth2::Service<Client, Connector>;
-async fn service() -> Service {
+type Client = hyper_util::client::legacy::Client<Connector, Full<Bytes>>;
+type Body = UnsyncBoxBody<Bytes, Infallible>;
+// pins the request body type, which the cassette layer alone leaves open
+type Service = tower::util::MapRequest<
+    crate::cassette::Service<crate::yup_oauth2::Service<Client, Connector>>,
+    fn(http::Request<Body>) -> http::Request<Body>,
+>;
+
+const BUCKET_NAME: &str = "bucket";
+const KMS_KEY_NAME: &str = "projects/project/locations/global/keyRings/key-ring/cryptoKeys/key";
+
+// talks to GCS and records src/api/tests/{test_name}.json when BUCKET_NAME is set, and replays
+// that cassette otherwise
+async fn service(test_name: &str) -> Service {
+    let path = format!(
+        "{}/src/api/tests/{test_name}.json",
+        env!("CARGO_MANIFEST_DIR"),
+    );
+    if env::var_os("BUCKET_NAME").is_none() {
+        return crate::cassette::Service::replay(&path)
+            .unwrap_or_else(|e| panic!("{path}: {e} (set BUCKET_NAME to record it)"))
+            .map_request(convert::identity);
+    }
+
     let tls_config = rustls::ClientConfig::builder_with_provider(Arc::new(
         rustls::crypto::aws_lc_rs::default_provider(),
     ))
@@ -29,24 +50,34 @@ async fn service() -> Service {
         .build();
     let client = hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
         .build(connector.clone());
-    crate::yup_oauth2::Layer::with_client(
+    let service = crate::yup_oauth2::Layer::with_client(
         hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
             .build(connector),
     )
     .await
     .unwrap()
-    .layer(client)
+    .layer(client);
+
+    let mut layer = crate::cassette::Layer::record(path).replace(bucket_name(), BUCKET_NAME);
+    if env::var_os("KMS_KEY_NAME").is_some() {
+        layer = layer.replace(kms_key_name(), KMS_KEY_NAME);
+    }
+    layer.layer(service).map_request(convert::identity)
 }
 
 fn bucket_name() -> String {
-    env::var("BUCKET_NAME").unwrap()
+    env::var("BUCKET_NAME").unwrap_or_else(|_| BUCKET_NAME.to_owned())
+}
+
+fn kms_key_name() -> String {
+    env::var("KMS_KEY_NAME").unwrap_or_else(|_| KMS_KEY_NAME.to_owned())
 }
 
 fn object_name() -> String {
     uuid::Uuid::new_v4().to_string()
 }
 
-fn body(data: &'static [u8]) -> UnsyncBoxBody<Bytes, Infallible> {
+fn body(data: &'static [u8]) -> Body {
     Full::from(Bytes::from_static(data)).boxed_unsync()
 }
 
@@ -60,7 +91,7 @@ fn assert_status<S, B>(e: super::Error<S, B>, status: StatusCode) {
 
 #[tokio::test]
 async fn test_xml_head_object_no_such_key() {
-    let service = service().await;
+    let service = service("test_xml_head_object_no_such_key").await;
     let bucket_name = bucket_name();
     let object_name = object_name();
     let e = super::xml::head_object::builder(&bucket_name, &object_name)
@@ -72,7 +103,7 @@ async fn test_xml_head_object_no_such_key() {
 
 #[tokio::test]
 async fn test_xml_get_object_no_such_key() {
-    let service = service().await;
+    let service = service("test_xml_get_object_no_such_key").await;
     let bucket_name = bucket_name();
     let object_name = object_name();
     let e = super::xml::get_object::builder(&bucket_name, &object_name)
@@ -84,7 +115,7 @@ async fn test_xml_get_object_no_such_key() {
 
 #[tokio::test]
 async fn test_xml_delete_object_no_such_key() {
-    let service = service().await;
+    let service = service("test_xml_delete_object_no_such_key").await;
     let bucket_name = bucket_name();
     let object_name = object_name();
     let e = super::xml::delete_object::builder(&bucket_name, &object_name)
@@ -96,7 +127,7 @@ async fn test_xml_delete_object_no_such_key() {
 
 #[tokio::test]
 async fn test_xml_put_object() {
-    let service = service().await;
+    let service = service("test_xml_put_object").await;
     let bucket_name = bucket_name();
     let object_name = object_name();
     let data = b"hello world";
@@ -135,7 +166,7 @@ async fn test_xml_put_object() {
 
 #[tokio::test]
 async fn test_xml_put_object_content_type() {
-    let service = service().await;
+    let service = service("test_xml_put_object_content_type").await;
     let bucket_name = bucket_name();
     let object_name = object_name();
     let data = b"hello world";
@@ -159,7 +190,7 @@ async fn test_xml_put_object_content_type() {
 
 #[tokio::test]
 async fn test_xml_put_object_bad_digest() {
-    let service = service().await;
+    let service = service("test_xml_put_object_bad_digest").await;
     let bucket_name = bucket_name();
     let object_name = object_name();
     let data = b"hello";
@@ -176,7 +207,7 @@ async fn test_xml_put_object_bad_digest() {
 
 #[tokio::test]
 async fn test_xml_delete_object() {
-    let service = service().await;
+    let service = service("test_xml_delete_object").await;
     let bucket_name = bucket_name();
     let object_name = object_name();
     let data = b"hello world";
@@ -210,7 +241,7 @@ async fn test_xml_delete_object() {
 
 #[tokio::test]
 async fn test_json_patch_object_content_type() {
-    let service = service().await;
+    let service = service("test_json_patch_object_content_type").await;
     let bucket_name = bucket_name();
     let object_name = object_name();
     let data = b"hello world";
@@ -253,3 +284,551 @@ async fn test_json_patch_object_content_type() {
         assert_eq!(content_type, ContentType::text_utf8());
     }
 }
+
+#[tokio::test]
+async fn test_xml_get_bucket_lifecycle() {
+    let service = service("test_xml_get_bucket_lifecycle").await;
+    let bucket_name = bucket_name();
+
+    let response = super::json::get_bucket::builder(&bucket_name)
+        .send(
+            service
+                .clone()
+                .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync)),
+        )
+        .await
+        .unwrap();
+    let lifecycle = response.into_body().lifecycle.unwrap_or_default();
+
+    let response = super::xml::get_bucket_lifecycle::builder(&bucket_name)
+        .send(service)
+        .await
+        .unwrap();
+    assert_eq!(response.into_body(), lifecycle);
+}
+
+#[tokio::test]
+async fn test_xml_get_bucket_cors() {
+    let service = service("test_xml_get_bucket_cors").await;
+    let bucket_name = bucket_name();
+
+    let response = super::json::get_bucket::builder(&bucket_name)
+        .send(
+            service
+                .clone()
+                .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync)),
+        )
+        .await
+        .unwrap();
+    let cors = response.into_body().cors;
+
+    let response = super::xml::get_bucket_cors::builder(&bucket_name)
+        .send(service)
+        .await
+        .unwrap();
+    assert_eq!(response.into_body(), cors);
+}
+
+#[tokio::test]
+async fn test_xml_get_bucket_website_config() {
+    let service = service("test_xml_get_bucket_website_config").await;
+    let bucket_name = bucket_name();
+
+    let response = super::json::get_bucket::builder(&bucket_name)
+        .send(
+            service
+                .clone()
+                .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync)),
+        )
+        .await
+        .unwrap();
+    let website = response.into_body().website.unwrap_or_default();
+
+    let response = super::xml::get_bucket_website_config::builder(&bucket_name)
+        .send(service)
+        .await
+        .unwrap();
+    assert_eq!(response.into_body(), website);
+}
+
+#[tokio::test]
+async fn test_xml_get_object_generation() {
+    let service = service("test_xml_get_object_generation").await;
+    let bucket_name = bucket_name();
+    let object_name = object_name();
+
+    let crate::header::XGoogGeneration(generation) = {
+        let response = super::xml::put_object::builder(&bucket_name, &object_name, body(b"hello"))
+            .send(service.clone())
+            .await
+            .unwrap();
+        response.headers().typed_get().unwrap()
+    };
+    {
+        super::xml::put_object::builder(&bucket_name, &object_name, body(b"world"))
+            .send(service.clone())
+            .await
+            .unwrap();
+    }
+    {
+        let e = super::xml::head_object::builder(&bucket_name, &object_name)
+            .generation(generation - 1)
+            .send(service.clone())
+            .await
+            .unwrap_err();
+        assert_status(e, StatusCode::NOT_FOUND);
+    }
+    {
+        let response = super::xml::head_object::builder(&bucket_name, &object_name)
+            .send(service.clone())
+            .await
+            .unwrap();
+        let crate::header::XGoogGeneration(latest) = response.headers().typed_get().unwrap();
+        assert!(latest > generation);
+        let response = super::xml::get_object::builder(&bucket_name, &object_name)
+            .generation(latest)
+            .send(service)
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, b"world".as_slice());
+    }
+}
+
+#[tokio::test]
+async fn test_xml_copy_object_source_generation() {
+    let service = service("test_xml_copy_object_source_generation").await;
+    let bucket_name = bucket_name();
+    let source_object_name = object_name();
+    let object_name = object_name();
+    let data = b"hello world";
+
+    let crate::header::XGoogGeneration(generation) = {
+        let response =
+            super::xml::put_object::builder(&bucket_name, &source_object_name, body(data))
+                .send(service.clone())
+                .await
+                .unwrap();
+        response.headers().typed_get().unwrap()
+    };
+    {
+        super::xml::copy_object::builder(
+            &bucket_name,
+            &object_name,
+            &bucket_name,
+            &source_object_name,
+        )
+        .source_generation(generation)
+        .send(service.clone())
+        .await
+        .unwrap();
+    }
+    {
+        let response = super::xml::get_object::builder(&bucket_name, &object_name)
+            .send(service)
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, data.as_slice());
+    }
+}
+
+#[tokio::test]
+async fn test_json_list_objects_versions() {
+    let service = service("test_json_list_objects_versions").await;
+    let bucket_name = bucket_name();
+    let object_name = object_name();
+
+    let crate::header::XGoogGeneration(generation) = {
+        let response = super::xml::put_object::builder(&bucket_name, &object_name, body(b"hello"))
+            .send(service.clone())
+            .await
+            .unwrap();
+        response.headers().typed_get().unwrap()
+    };
+    {
+        let response =
+            super::json::list_objects::builder(&bucket_name)
+                .prefix(&object_name)
+                .versions(true)
+                .send(service.map_request(|request: http::Request<String>| {
+                    request.map(BodyExt::boxed_unsync)
+                }))
+                .await
+                .unwrap();
+        let items = response.into_body().items;
+        assert!(items
+            .iter()
+            .any(|item| item.name == object_name && item.generation == generation));
+    }
+}
+
+#[tokio::test]
+async fn test_xml_get_bucket_versioning() {
+    let service = service("test_xml_get_bucket_versioning").await;
+    let bucket_name = bucket_name();
+
+    let response = super::json::get_bucket::builder(&bucket_name)
+        .send(
+            service
+                .clone()
+                .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync)),
+        )
+        .await
+        .unwrap();
+    let versioning = response.into_body().versioning.unwrap_or_default();
+
+    let response = super::xml::get_bucket_versioning::builder(&bucket_name)
+        .send(service)
+        .await
+        .unwrap();
+    assert_eq!(response.into_body(), versioning);
+}
+
+#[tokio::test]
+async fn test_json_restore_object() {
+    let service = service("test_json_restore_object").await;
+    let json_service = service
+        .clone()
+        .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync));
+    let bucket_name = bucket_name();
+    let object_name = object_name();
+    let data = b"hello world";
+
+    let crate::header::XGoogGeneration(generation) = {
+        let response = super::xml::put_object::builder(&bucket_name, &object_name, body(data))
+            .send(service.clone())
+            .await
+            .unwrap();
+        response.headers().typed_get().unwrap()
+    };
+    {
+        super::xml::delete_object::builder(&bucket_name, &object_name)
+            .send(service.clone())
+            .await
+            .unwrap();
+    }
+    {
+        let response = super::json::list_objects::builder(&bucket_name)
+            .prefix(&object_name)
+            .soft_deleted(true)
+            .send(json_service.clone())
+            .await
+            .unwrap();
+        let items = response.into_body().items;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].generation, generation);
+        assert!(items[0].soft_delete_time.is_some());
+    }
+    {
+        let response = super::json::get_object::builder(&bucket_name, &object_name)
+            .generation(generation)
+            .soft_deleted(true)
+            .send(json_service.clone())
+            .await
+            .unwrap();
+        assert_eq!(response.into_body().generation, generation);
+    }
+    {
+        super::json::restore_object::builder(&bucket_name, &object_name, generation)
+            .if_generation_match(0)
+            .send(json_service)
+            .await
+            .unwrap();
+    }
+    {
+        let response = super::xml::get_object::builder(&bucket_name, &object_name)
+            .send(service)
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, data.as_slice());
+    }
+}
+
+#[tokio::test]
+async fn test_json_bulk_restore_objects() {
+    let service = service("test_json_bulk_restore_objects").await;
+    let json_service = service
+        .clone()
+        .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync));
+    let bucket_name = bucket_name();
+    let object_name = object_name();
+    let data = b"hello world";
+
+    {
+        super::xml::put_object::builder(&bucket_name, &object_name, body(data))
+            .send(service.clone())
+            .await
+            .unwrap();
+    }
+    {
+        super::xml::delete_object::builder(&bucket_name, &object_name)
+            .send(service.clone())
+            .await
+            .unwrap();
+    }
+    let operation = {
+        let request = super::json::bulk_restore_objects::Request {
+            match_globs: vec![object_name.clone()],
+            ..super::json::bulk_restore_objects::Request::default()
+        };
+        let response = super::json::bulk_restore_objects::builder(&bucket_name, request)
+            .send(json_service.clone())
+            .await
+            .unwrap();
+        response.into_body()
+    };
+    {
+        let response = super::json::list_operations::builder(&bucket_name)
+            .send(json_service.clone())
+            .await
+            .unwrap();
+        assert!(response
+            .into_body()
+            .operations
+            .iter()
+            .any(|item| item.name == operation.name));
+    }
+    {
+        let response = super::json::get_operation::builder(&bucket_name, operation.id())
+            .poll_until_done(json_service, tokio::time::sleep)
+            .await
+            .unwrap();
+        let operation = response.into_body();
+        assert!(operation.done);
+        assert!(operation.error.is_none());
+    }
+    {
+        let response = super::xml::get_object::builder(&bucket_name, &object_name)
+            .send(service)
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, data.as_slice());
+    }
+}
+
+#[tokio::test]
+async fn test_object_acl_private() {
+    let service = service("test_object_acl_private").await;
+    let bucket_name = bucket_name();
+    let object_name = object_name();
+
+    {
+        super::xml::put_object::builder(&bucket_name, &object_name, body(b"hello world"))
+            .typed_header(crate::header::XGoogAcl(crate::acl::PredefinedAcl::Private))
+            .send(service.clone())
+            .await
+            .unwrap();
+    }
+    let items = {
+        let response =
+            super::json::list_object_access_controls::builder(&bucket_name, &object_name)
+                .send(
+                    service
+                        .clone()
+                        .map_request(|request: http::Request<String>| {
+                            request.map(BodyExt::boxed_unsync)
+                        }),
+                )
+                .await
+                .unwrap();
+        response.into_body().items
+    };
+    assert!(items
+        .iter()
+        .all(|item| item.role == crate::acl::Role::Owner));
+    {
+        let response = super::xml::get_object_acl::builder(&bucket_name, &object_name)
+            .send(service)
+            .await
+            .unwrap();
+        let entries = response.into_body().entries.entry;
+        assert_eq!(entries.len(), items.len());
+        assert!(entries
+            .iter()
+            .all(|entry| entry.permission
+                == super::xml::access_control_list::Permission::FullControl));
+    }
+}
+
+#[tokio::test]
+async fn test_json_bucket_iam_policy() {
+    let service = service("test_json_bucket_iam_policy")
+        .await
+        .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync));
+    let bucket_name = bucket_name();
+
+    let policy = {
+        let response = super::json::get_bucket_iam_policy::builder(&bucket_name)
+            .options_requested_policy_version(3)
+            .send(service.clone())
+            .await
+            .unwrap();
+        response.into_body()
+    };
+    assert!(policy.etag.is_some());
+    {
+        let response = super::json::set_bucket_iam_policy::read_modify_write(
+            &bucket_name,
+            service.clone(),
+            tokio::time::sleep,
+            |_| false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.into_body().bindings, policy.bindings);
+    }
+    {
+        let response = super::json::test_bucket_iam_permissions::builder(
+            &bucket_name,
+            ["storage.buckets.getIamPolicy", "storage.objects.create"],
+        )
+        .send(service)
+        .await
+        .unwrap();
+        assert!(response
+            .into_body()
+            .permissions
+            .iter()
+            .any(|permission| permission == "storage.buckets.getIamPolicy"));
+    }
+}
+
+#[tokio::test]
+async fn test_json_list_notifications() {
+    let service = service("test_json_list_notifications")
+        .await
+        .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync));
+    let bucket_name = bucket_name();
+
+    let items = {
+        let response = super::json::list_notifications::builder(&bucket_name)
+            .send(service.clone())
+            .await
+            .unwrap();
+        response.into_body().items
+    };
+    for item in items {
+        let response =
+            super::json::get_notification::builder(&bucket_name, item.id.as_deref().unwrap())
+                .send(service.clone())
+                .await
+                .unwrap();
+        assert_eq!(response.into_body(), item);
+    }
+}
+
+#[tokio::test]
+async fn test_customer_encryption_key() {
+    let service = service("test_customer_encryption_key").await;
+    let json_service = service
+        .clone()
+        .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync));
+    let bucket_name = bucket_name();
+    let object_name = object_name();
+    let data = b"hello world";
+    // fixed keys so that the key hashes in a replayed cassette match
+    let key = crate::encryption::CustomerEncryptionKey::new([1; 32]);
+    let new_key = crate::encryption::CustomerEncryptionKey::new([2; 32]);
+
+    super::xml::put_object::builder(&bucket_name, &object_name, body(data))
+        .customer_encryption_key(key.clone())
+        .send(service.clone())
+        .await
+        .unwrap();
+    {
+        let e = super::xml::get_object::builder(&bucket_name, &object_name)
+            .send(service.clone())
+            .await
+            .unwrap_err();
+        assert_status(e, StatusCode::BAD_REQUEST);
+    }
+    {
+        let response = super::xml::get_object::builder(&bucket_name, &object_name)
+            .customer_encryption_key(key.clone())
+            .send(service.clone())
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, data.as_slice());
+    }
+    {
+        let response = super::json::rewrite_object::send_until_done(
+            super::json::rewrite_object::builder(
+                &bucket_name,
+                &object_name,
+                &bucket_name,
+                &object_name,
+            )
+            .source_customer_encryption_key(key.clone())
+            .customer_encryption_key(new_key.clone()),
+            json_service.clone(),
+        )
+        .await
+        .unwrap();
+        let customer_encryption = response.into_body().customer_encryption.unwrap();
+        assert_eq!(customer_encryption.key_sha256, new_key.key_sha256());
+    }
+    {
+        let response = super::xml::head_object::builder(&bucket_name, &object_name)
+            .customer_encryption_key(new_key.clone())
+            .send(service.clone())
+            .await
+            .unwrap();
+        let ContentLength(content_length) = response.headers().typed_get().unwrap();
+        assert_eq!(content_length, data.len() as u64);
+    }
+}
+
+#[tokio::test]
+async fn test_kms_key_name() {
+    let service = service("test_kms_key_name").await;
+    let json_service = service
+        .clone()
+        .map_request(|request: http::Request<String>| request.map(BodyExt::boxed_unsync));
+    let bucket_name = bucket_name();
+    let kms_key_name = kms_key_name();
+    let data = b"hello world";
+
+    {
+        let response = super::xml::put_object::builder(&bucket_name, object_name(), body(data))
+            .typed_header(crate::header::XGoogEncryptionKmsKeyName(
+                kms_key_name.clone(),
+            ))
+            .send(service.clone())
+            .await
+            .unwrap();
+        let crate::header::XGoogEncryptionKmsKeyName(value) =
+            response.headers().typed_get().unwrap();
+        let (key, version) = crate::encryption::split_kms_key_version(&value);
+        assert_eq!(key, kms_key_name);
+        assert!(version.is_some());
+    }
+    {
+        let response = super::json::insert_object::builder(&bucket_name, object_name(), body(data))
+            .kms_key_name(&kms_key_name)
+            .send(service.clone())
+            .await
+            .unwrap();
+        let object = response.into_body();
+        let (key, _) =
+            crate::encryption::split_kms_key_version(object.kms_key_name.as_deref().unwrap());
+        assert_eq!(key, kms_key_name);
+
+        let response = super::json::compose_object::builder(
+            &bucket_name,
+            object_name(),
+            vec![super::json::compose_object::SourceObject::new(&object.name)],
+        )
+        .kms_key_name(&kms_key_name)
+        .send(json_service.clone())
+        .await
+        .unwrap();
+        let (key, _) = crate::encryption::split_kms_key_version(
+            response.body().kms_key_name.as_deref().unwrap(),
+        );
+        assert_eq!(key, kms_key_name);
+    }
+}
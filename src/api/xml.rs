@@ -1,18 +1,51 @@
+pub mod access_control_list;
+pub mod copy_object;
+mod cors_config;
 pub mod delete_object;
+pub mod get_bucket_acl;
+pub mod get_bucket_cors;
+pub mod get_bucket_lifecycle;
+pub mod get_bucket_versioning;
+pub mod get_bucket_website_config;
 pub mod get_object;
+pub mod get_object_acl;
 pub mod head_object;
+mod lifecycle_configuration;
+pub mod list_objects;
+pub mod put_bucket_acl;
+pub mod put_bucket_cors;
+pub mod put_bucket_lifecycle;
+pub mod put_bucket_versioning;
+pub mod put_bucket_website_config;
 pub mod put_object;
+pub mod put_object_acl;
+mod versioning_configuration;
+mod website_configuration;
 
+use super::query;
 use futures::future::{Either, MapErr, MapOk};
 use futures::{FutureExt, TryFutureExt};
 use http::{Request, Response};
 use http_body::Body;
+use http_body_util::combinators::Collect;
+use http_body_util::BodyExt;
 use http_extra::check_status;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::future::{self, Future, Ready};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
 use tower::util::Oneshot;
 use tower::{Service, ServiceBuilder, ServiceExt};
 
+fn bucket_uri<B>(bucket_name: B, query: &str) -> String
+where
+    B: fmt::Display,
+{
+    format!("https://{bucket_name}.storage.googleapis.com/?{query}")
+}
+
 fn uri<B, O>(bucket_name: B, object_name: O) -> String
 where
     B: fmt::Display,
@@ -53,6 +86,25 @@ type MapErrFn<S, T, U> = fn(
 ) -> Error<S, T, U>;
 type Error<S, T, U> = super::Error<<S as Service<Request<T>>>::Error, <U as Body>::Error>;
 
+fn send_xml<S, T, U, V>(service: S, builder: http::request::Builder, body: V) -> SendXml<S, T, U>
+where
+    S: Service<Request<T>, Response = Response<U>>,
+    T: From<String>,
+    U: Body,
+    V: Serialize,
+{
+    match quick_xml::se::to_string(&body) {
+        Ok(body) => send(
+            service,
+            builder.header(http::header::CONTENT_TYPE, "application/xml"),
+            T::from(body),
+        )
+        .left_future(),
+        Err(e) => future::ready(Err(Error::<S, T, U>::XmlSe(e))).right_future(),
+    }
+}
+type SendXml<S, T, U> = Either<Send<S, T, U>, Ready<Result<Response<U>, Error<S, T, U>>>>;
+
 fn empty<F, B, E>(f: F) -> Empty<F, B>
 where
     F: Future<Output = Result<Response<B>, E>>,
@@ -60,3 +112,60 @@ where
     f.map_ok(|response| response.map(|_| ()))
 }
 type Empty<F, B> = MapOk<F, fn(Response<B>) -> Response<()>>;
+
+fn from_xml<F, B, W>(f: F) -> FromXml<F, B, W>
+where
+    B: Body,
+{
+    FromXml(FromXmlState::S0 { f }, PhantomData)
+}
+
+#[pin_project::pin_project]
+pub struct FromXml<F, B, W>(#[pin] FromXmlState<F, B>, PhantomData<fn() -> W>)
+where
+    B: Body;
+
+#[pin_project::pin_project(project = FromXmlStateProj)]
+enum FromXmlState<F, B>
+where
+    B: Body,
+{
+    S0 {
+        #[pin]
+        f: F,
+    },
+    S1 {
+        parts: Option<http::response::Parts>,
+        f: Pin<Box<Collect<B>>>,
+    },
+}
+
+impl<F, B, W, E> Future for FromXml<F, B, W>
+where
+    F: Future<Output = Result<Response<B>, super::Error<E, B::Error>>>,
+    B: Body,
+    W: for<'de> Deserialize<'de>,
+{
+    type Output = Result<Response<W>, super::Error<E, B::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            match this.0.as_mut().project() {
+                FromXmlStateProj::S0 { f } => {
+                    let (parts, body) = ready!(f.poll(cx))?.into_parts();
+                    this.0.set(FromXmlState::S1 {
+                        parts: Some(parts),
+                        f: Box::pin(body.collect()),
+                    });
+                }
+                FromXmlStateProj::S1 { parts, f } => {
+                    let body = ready!(f.as_mut().poll(cx)).map_err(super::Error::Body)?;
+                    let body = quick_xml::de::from_reader(bytes::Buf::reader(body.aggregate()))
+                        .map_err(super::Error::XmlDe)?;
+                    break Poll::Ready(Ok(Response::from_parts(parts.take().unwrap(), body)));
+                }
+            }
+        }
+    }
+}
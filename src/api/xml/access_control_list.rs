@@ -0,0 +1,137 @@
+// https://cloud.google.com/storage/docs/xml-api/put-object-acls#request_body_elements
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename = "AccessControlList", rename_all = "PascalCase")]
+pub struct AccessControlList {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Owner>,
+    pub entries: Entries,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Owner {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Entries {
+    #[serde(default, rename = "Entry")]
+    pub entry: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Entry {
+    pub scope: Scope,
+    pub permission: Permission,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Scope {
+    #[serde(rename = "@type")]
+    pub r#type: ScopeType,
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum ScopeType {
+    UserById,
+    UserByEmail,
+    GroupById,
+    GroupByEmail,
+    GroupByDomain,
+    AllUsers,
+    AllAuthenticatedUsers,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Permission {
+    Read,
+    Write,
+    FullControl,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessControlList, Entries, Entry, Owner, Permission, Scope, ScopeType};
+
+    #[test]
+    fn test_access_control_list() {
+        let document = concat!(
+            "<AccessControlList>",
+            "<Owner><ID>00b4903a9721</ID><Name>owner</Name></Owner>",
+            "<Entries>",
+            "<Entry>",
+            r#"<Scope type="UserById"><ID>00b4903a9721</ID><Name>owner</Name></Scope>"#,
+            "<Permission>FULL_CONTROL</Permission>",
+            "</Entry>",
+            "<Entry>",
+            r#"<Scope type="GroupByDomain"><Domain>example.com</Domain></Scope>"#,
+            "<Permission>READ</Permission>",
+            "</Entry>",
+            "<Entry>",
+            r#"<Scope type="AllUsers"/>"#,
+            "<Permission>READ</Permission>",
+            "</Entry>",
+            "</Entries>",
+            "</AccessControlList>",
+        );
+        let scope = |r#type| Scope {
+            r#type,
+            id: None,
+            email_address: None,
+            domain: None,
+            name: None,
+        };
+        let access_control_list = AccessControlList {
+            owner: Some(Owner {
+                id: "00b4903a9721".to_owned(),
+                name: Some("owner".to_owned()),
+            }),
+            entries: Entries {
+                entry: vec![
+                    Entry {
+                        scope: Scope {
+                            id: Some("00b4903a9721".to_owned()),
+                            name: Some("owner".to_owned()),
+                            ..scope(ScopeType::UserById)
+                        },
+                        permission: Permission::FullControl,
+                    },
+                    Entry {
+                        scope: Scope {
+                            domain: Some("example.com".to_owned()),
+                            ..scope(ScopeType::GroupByDomain)
+                        },
+                        permission: Permission::Read,
+                    },
+                    Entry {
+                        scope: scope(ScopeType::AllUsers),
+                        permission: Permission::Read,
+                    },
+                ],
+            },
+        };
+        assert_eq!(
+            quick_xml::de::from_str::<AccessControlList>(document).unwrap(),
+            access_control_list,
+        );
+        assert_eq!(
+            quick_xml::se::to_string(&access_control_list).unwrap(),
+            document,
+        );
+    }
+}
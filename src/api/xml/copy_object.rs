@@ -0,0 +1,128 @@
+// https://cloud.google.com/storage/docs/xml-api/put-object-copy
+
+use crate::encryption::CustomerEncryptionKey;
+use headers::{Header, HeaderMapExt};
+use http::{HeaderMap, HeaderName, HeaderValue, Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B, O, SB, SO>(
+    bucket_name: B,
+    object_name: O,
+    source_bucket_name: SB,
+    source_object_name: SO,
+) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+    SB: Into<String>,
+    SO: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        object_name: object_name.into(),
+        source_bucket_name: source_bucket_name.into(),
+        source_object_name: source_object_name.into(),
+        source_generation: None,
+        customer_encryption_key: None,
+        source_customer_encryption_key: None,
+        headers: HeaderMap::new(),
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    object_name: String,
+    source_bucket_name: String,
+    source_object_name: String,
+    source_generation: Option<u64>,
+    customer_encryption_key: Option<CustomerEncryptionKey>,
+    source_customer_encryption_key: Option<CustomerEncryptionKey>,
+    headers: HeaderMap,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: Default,
+        U: Body,
+    {
+        static X_GOOG_COPY_SOURCE: HeaderName = HeaderName::from_static("x-goog-copy-source");
+        static X_GOOG_COPY_SOURCE_GENERATION: HeaderName =
+            HeaderName::from_static("x-goog-copy-source-generation");
+
+        let Self {
+            bucket_name,
+            object_name,
+            source_bucket_name,
+            source_object_name,
+            source_generation,
+            customer_encryption_key,
+            source_customer_encryption_key,
+            headers,
+        } = self;
+        let mut builder = Request::put(super::uri(bucket_name, object_name));
+        if let Some(h) = builder.headers_mut() {
+            *h = headers;
+        }
+        let builder = builder.header(
+            &X_GOOG_COPY_SOURCE,
+            format!(
+                "/{source_bucket_name}/{}",
+                percent_encoding::percent_encode(
+                    source_object_name.as_bytes(),
+                    percent_encoding::NON_ALPHANUMERIC,
+                ),
+            ),
+        );
+        let builder = if let Some(source_generation) = source_generation {
+            builder.header(
+                &X_GOOG_COPY_SOURCE_GENERATION,
+                HeaderValue::from(source_generation),
+            )
+        } else {
+            builder
+        };
+        let builder = crate::encryption::with_headers(
+            builder,
+            customer_encryption_key
+                .iter()
+                .flat_map(CustomerEncryptionKey::headers)
+                .chain(
+                    source_customer_encryption_key
+                        .iter()
+                        .flat_map(CustomerEncryptionKey::copy_source_headers),
+                ),
+        );
+        super::empty(super::send(service, builder, T::default()))
+    }
+}
+pub type Future<S, T, U> = super::Empty<super::Send<S, T, U>, U>;
+
+impl Builder {
+    // the key for the destination object
+    pub fn customer_encryption_key(mut self, key: CustomerEncryptionKey) -> Self {
+        self.customer_encryption_key = Some(key);
+        self
+    }
+
+    // required if the source object is encrypted with a customer-supplied key
+    pub fn source_customer_encryption_key(mut self, key: CustomerEncryptionKey) -> Self {
+        self.source_customer_encryption_key = Some(key);
+        self
+    }
+
+    pub fn source_generation(mut self, source_generation: u64) -> Self {
+        self.source_generation = Some(source_generation);
+        self
+    }
+
+    pub fn typed_header<H>(mut self, header: H) -> Self
+    where
+        H: Header,
+    {
+        self.headers.typed_insert(header);
+        self
+    }
+}
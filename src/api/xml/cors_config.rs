@@ -0,0 +1,164 @@
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-cors#request_body_elements
+
+use crate::bucket;
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename = "CorsConfig")]
+pub struct CorsConfig {
+    #[serde(default, rename = "Cors")]
+    cors: Vec<Cors>,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Cors {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origins: Option<Origins>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    methods: Option<Methods>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_headers: Option<ResponseHeaders>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_age_sec: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+struct Origins {
+    #[serde(default, rename = "Origin")]
+    origin: Vec<String>,
+}
+
+#[serde_with::serde_as]
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+struct Methods {
+    #[serde_as(as = "Vec<serde_with::DisplayFromStr>")]
+    #[serde(default, rename = "Method")]
+    method: Vec<http::Method>,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+struct ResponseHeaders {
+    #[serde(default, rename = "ResponseHeader")]
+    response_header: Vec<String>,
+}
+
+impl From<Vec<bucket::Cors>> for CorsConfig {
+    fn from(value: Vec<bucket::Cors>) -> Self {
+        Self {
+            cors: value.into_iter().map(Cors::from).collect(),
+        }
+    }
+}
+
+impl From<CorsConfig> for Vec<bucket::Cors> {
+    fn from(value: CorsConfig) -> Self {
+        value.cors.into_iter().map(bucket::Cors::from).collect()
+    }
+}
+
+impl From<bucket::Cors> for Cors {
+    fn from(value: bucket::Cors) -> Self {
+        let bucket::Cors {
+            max_age_seconds,
+            method,
+            origin,
+            response_header,
+        } = value;
+        Self {
+            origins: (!origin.is_empty()).then_some(Origins { origin }),
+            methods: (!method.is_empty()).then_some(Methods { method }),
+            response_headers: (!response_header.is_empty())
+                .then_some(ResponseHeaders { response_header }),
+            max_age_sec: max_age_seconds,
+        }
+    }
+}
+
+impl From<Cors> for bucket::Cors {
+    fn from(value: Cors) -> Self {
+        let Cors {
+            origins,
+            methods,
+            response_headers,
+            max_age_sec,
+        } = value;
+        Self {
+            max_age_seconds: max_age_sec,
+            method: methods.map(|value| value.method).unwrap_or_default(),
+            origin: origins.map(|value| value.origin).unwrap_or_default(),
+            response_header: response_headers
+                .map(|value| value.response_header)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bucket::Cors;
+    use http::Method;
+
+    const DOCUMENT: &str = concat!(
+        "<CorsConfig>",
+        "<Cors>",
+        "<Origins>",
+        "<Origin>https://example.com</Origin>",
+        "<Origin>https://www.example.com</Origin>",
+        "</Origins>",
+        "<Methods><Method>GET</Method><Method>HEAD</Method></Methods>",
+        "<ResponseHeaders><ResponseHeader>Content-Type</ResponseHeader></ResponseHeaders>",
+        "<MaxAgeSec>3600</MaxAgeSec>",
+        "</Cors>",
+        "<Cors>",
+        "<Origins><Origin>*</Origin></Origins>",
+        "</Cors>",
+        "</CorsConfig>",
+    );
+
+    fn cors() -> Vec<Cors> {
+        vec![
+            Cors {
+                max_age_seconds: Some(3600),
+                method: vec![Method::GET, Method::HEAD],
+                origin: vec![
+                    "https://example.com".to_owned(),
+                    "https://www.example.com".to_owned(),
+                ],
+                response_header: vec!["Content-Type".to_owned()],
+            },
+            Cors {
+                origin: vec!["*".to_owned()],
+                ..Cors::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_cors_config_deserialize() {
+        let value = quick_xml::de::from_str::<super::CorsConfig>(DOCUMENT).unwrap();
+        assert_eq!(Vec::<Cors>::from(value), cors());
+    }
+
+    #[test]
+    fn test_cors_config_serialize() {
+        let value = super::CorsConfig::from(cors());
+        assert_eq!(quick_xml::se::to_string(&value).unwrap(), DOCUMENT);
+    }
+
+    #[test]
+    fn test_cors_json() {
+        let value = serde_json::json!([
+            {
+                "maxAgeSeconds": 3600,
+                "method": ["GET", "HEAD"],
+                "origin": ["https://example.com", "https://www.example.com"],
+                "responseHeader": ["Content-Type"],
+            },
+            {
+                "origin": ["*"],
+            },
+        ]);
+        assert_eq!(serde_json::to_value(cors()).unwrap(), value);
+        assert_eq!(serde_json::from_value::<Vec<Cors>>(value).unwrap(), cors());
+    }
+}
@@ -12,12 +12,14 @@ where
     Builder {
         bucket_name: bucket_name.into(),
         object_name: object_name.into(),
+        generation: None,
     }
 }
 
 pub struct Builder {
     bucket_name: String,
     object_name: String,
+    generation: Option<u64>,
 }
 
 impl Builder {
@@ -30,12 +32,20 @@ impl Builder {
         let Self {
             bucket_name,
             object_name,
+            generation,
         } = self;
-        super::empty(super::send(
-            service,
-            Request::delete(super::uri(bucket_name, object_name)),
-            T::default(),
-        ))
+        let uri = super::query(
+            super::uri(bucket_name, object_name),
+            generation.map(|generation| ("generation", generation.to_string())),
+        );
+        super::empty(super::send(service, Request::delete(uri), T::default()))
     }
 }
 pub type Future<S, T, U> = super::Empty<super::Send<S, T, U>, U>;
+
+impl Builder {
+    pub fn generation(mut self, generation: u64) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+}
@@ -0,0 +1,43 @@
+// https://cloud.google.com/storage/docs/xml-api/get-bucket-lifecycle
+
+use super::lifecycle_configuration::LifecycleConfiguration;
+use crate::bucket::Lifecycle;
+use futures::future::MapOk;
+use futures::TryFutureExt;
+use http::{Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B>(bucket_name: B) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: Default,
+        U: Body,
+    {
+        let Self { bucket_name } = self;
+        super::from_xml(super::send(
+            service,
+            Request::get(super::bucket_uri(bucket_name, "lifecycle")),
+            T::default(),
+        ))
+        .map_ok(|response| response.map(Lifecycle::from))
+    }
+}
+pub type Future<S, T, U> = MapOk<
+    super::FromXml<super::Send<S, T, U>, U, LifecycleConfiguration>,
+    fn(Response<LifecycleConfiguration>) -> Response<Lifecycle>,
+>;
@@ -1,5 +1,6 @@
 // https://cloud.google.com/storage/docs/xml-api/get-object-download
 
+use crate::encryption::CustomerEncryptionKey;
 use http::{Request, Response};
 use http_body::Body;
 use tower::Service;
@@ -12,12 +13,16 @@ where
     Builder {
         bucket_name: bucket_name.into(),
         object_name: object_name.into(),
+        customer_encryption_key: None,
+        generation: None,
     }
 }
 
 pub struct Builder {
     bucket_name: String,
     object_name: String,
+    customer_encryption_key: Option<CustomerEncryptionKey>,
+    generation: Option<u64>,
 }
 
 impl Builder {
@@ -30,12 +35,33 @@ impl Builder {
         let Self {
             bucket_name,
             object_name,
+            customer_encryption_key,
+            generation,
         } = self;
-        super::send(
-            service,
-            Request::get(super::uri(bucket_name, object_name)),
-            T::default(),
-        )
+        let uri = super::query(
+            super::uri(bucket_name, object_name),
+            generation.map(|generation| ("generation", generation.to_string())),
+        );
+        let builder = crate::encryption::with_headers(
+            Request::get(uri),
+            customer_encryption_key
+                .iter()
+                .flat_map(CustomerEncryptionKey::headers),
+        );
+        super::send(service, builder, T::default())
     }
 }
 pub type Future<S, T, U> = super::Send<S, T, U>;
+
+impl Builder {
+    // required if the object is encrypted with a customer-supplied key
+    pub fn customer_encryption_key(mut self, key: CustomerEncryptionKey) -> Self {
+        self.customer_encryption_key = Some(key);
+        self
+    }
+
+    pub fn generation(mut self, generation: u64) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+}
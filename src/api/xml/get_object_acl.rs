@@ -0,0 +1,52 @@
+// https://cloud.google.com/storage/docs/xml-api/get-object-acls
+
+use super::access_control_list::AccessControlList;
+use http::{Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B, O>(bucket_name: B, object_name: O) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        object_name: object_name.into(),
+        generation: None,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    object_name: String,
+    generation: Option<u64>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: Default,
+        U: Body,
+    {
+        let Self {
+            bucket_name,
+            object_name,
+            generation,
+        } = self;
+        let uri = super::query(
+            format!("{}?acl", super::uri(bucket_name, object_name)),
+            generation.map(|generation| ("generation", generation.to_string())),
+        );
+        super::from_xml(super::send(service, Request::get(uri), T::default()))
+    }
+}
+pub type Future<S, T, U> = super::FromXml<super::Send<S, T, U>, U, AccessControlList>;
+
+impl Builder {
+    pub fn generation(mut self, generation: u64) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+}
@@ -0,0 +1,278 @@
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-lifecycle#request_body_elements
+
+use crate::bucket::{self, StorageClass};
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename = "LifecycleConfiguration")]
+pub struct LifecycleConfiguration {
+    #[serde(default, rename = "Rule")]
+    rule: Vec<Rule>,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Rule {
+    action: Action,
+    condition: Condition,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+struct Action {
+    #[serde(rename = "$value")]
+    value: ActionValue,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+enum ActionValue {
+    Delete,
+    SetStorageClass(StorageClass),
+    AbortIncompleteMultipartUpload,
+}
+
+// enums in a sequence of elements are otherwise tagged by the element name
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+struct StorageClassText {
+    #[serde(rename = "$text")]
+    value: StorageClass,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Condition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    custom_time_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    days_since_custom_time: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    days_since_noncurrent_time: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_live: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    matches_prefix: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    matches_storage_class: Vec<StorageClassText>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    matches_suffix: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    noncurrent_time_before: Option<String>,
+    #[serde(
+        rename = "NumberOfNewerVersions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    num_newer_versions: Option<u32>,
+}
+
+impl From<bucket::Lifecycle> for LifecycleConfiguration {
+    fn from(value: bucket::Lifecycle) -> Self {
+        Self {
+            rule: value.rule.into_iter().map(Rule::from).collect(),
+        }
+    }
+}
+
+impl From<LifecycleConfiguration> for bucket::Lifecycle {
+    fn from(value: LifecycleConfiguration) -> Self {
+        Self {
+            rule: value.rule.into_iter().map(bucket::Rule::from).collect(),
+        }
+    }
+}
+
+impl From<bucket::Rule> for Rule {
+    fn from(value: bucket::Rule) -> Self {
+        let action = match value.action {
+            bucket::Action::Delete => ActionValue::Delete,
+            bucket::Action::SetStorageClass { storage_class } => {
+                ActionValue::SetStorageClass(storage_class)
+            }
+            bucket::Action::AbortIncompleteMultipartUpload => {
+                ActionValue::AbortIncompleteMultipartUpload
+            }
+        };
+        let bucket::Condition {
+            age,
+            created_before,
+            custom_time_before,
+            days_since_custom_time,
+            days_since_noncurrent_time,
+            is_live,
+            matches_prefix,
+            matches_storage_class,
+            matches_suffix,
+            noncurrent_time_before,
+            num_newer_versions,
+        } = value.condition;
+        Self {
+            action: Action { value: action },
+            condition: Condition {
+                age,
+                created_before,
+                custom_time_before,
+                days_since_custom_time,
+                days_since_noncurrent_time,
+                is_live,
+                matches_prefix,
+                matches_storage_class: matches_storage_class
+                    .into_iter()
+                    .map(|value| StorageClassText { value })
+                    .collect(),
+                matches_suffix,
+                noncurrent_time_before,
+                num_newer_versions,
+            },
+        }
+    }
+}
+
+impl From<Rule> for bucket::Rule {
+    fn from(value: Rule) -> Self {
+        let action = match value.action.value {
+            ActionValue::Delete => bucket::Action::Delete,
+            ActionValue::SetStorageClass(storage_class) => {
+                bucket::Action::SetStorageClass { storage_class }
+            }
+            ActionValue::AbortIncompleteMultipartUpload => {
+                bucket::Action::AbortIncompleteMultipartUpload
+            }
+        };
+        let Condition {
+            age,
+            created_before,
+            custom_time_before,
+            days_since_custom_time,
+            days_since_noncurrent_time,
+            is_live,
+            matches_prefix,
+            matches_storage_class,
+            matches_suffix,
+            noncurrent_time_before,
+            num_newer_versions,
+        } = value.condition;
+        Self {
+            action,
+            condition: bucket::Condition {
+                age,
+                created_before,
+                custom_time_before,
+                days_since_custom_time,
+                days_since_noncurrent_time,
+                is_live,
+                matches_prefix,
+                matches_storage_class: matches_storage_class
+                    .into_iter()
+                    .map(|StorageClassText { value }| value)
+                    .collect(),
+                matches_suffix,
+                noncurrent_time_before,
+                num_newer_versions,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bucket::{Action, Condition, Lifecycle, Rule, StorageClass};
+
+    const DOCUMENT: &str = concat!(
+        "<LifecycleConfiguration>",
+        "<Rule>",
+        "<Action><SetStorageClass>NEARLINE</SetStorageClass></Action>",
+        "<Condition>",
+        "<Age>365</Age>",
+        "<MatchesStorageClass>MULTI_REGIONAL</MatchesStorageClass>",
+        "<MatchesStorageClass>STANDARD</MatchesStorageClass>",
+        "</Condition>",
+        "</Rule>",
+        "<Rule>",
+        "<Action><Delete/></Action>",
+        "<Condition>",
+        "<IsLive>false</IsLive>",
+        "<MatchesPrefix>logs/</MatchesPrefix>",
+        "<NumberOfNewerVersions>3</NumberOfNewerVersions>",
+        "</Condition>",
+        "</Rule>",
+        "<Rule>",
+        "<Action><AbortIncompleteMultipartUpload/></Action>",
+        "<Condition><Age>7</Age></Condition>",
+        "</Rule>",
+        "</LifecycleConfiguration>",
+    );
+
+    fn lifecycle() -> Lifecycle {
+        Lifecycle {
+            rule: vec![
+                Rule {
+                    action: Action::SetStorageClass {
+                        storage_class: StorageClass::Nearline,
+                    },
+                    condition: Condition {
+                        age: Some(365),
+                        matches_storage_class: vec![
+                            StorageClass::MultiRegional,
+                            StorageClass::Standard,
+                        ],
+                        ..Condition::default()
+                    },
+                },
+                Rule {
+                    action: Action::Delete,
+                    condition: Condition {
+                        is_live: Some(false),
+                        matches_prefix: vec!["logs/".to_owned()],
+                        num_newer_versions: Some(3),
+                        ..Condition::default()
+                    },
+                },
+                Rule {
+                    action: Action::AbortIncompleteMultipartUpload,
+                    condition: Condition {
+                        age: Some(7),
+                        ..Condition::default()
+                    },
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_configuration_deserialize() {
+        let value = quick_xml::de::from_str::<super::LifecycleConfiguration>(DOCUMENT).unwrap();
+        assert_eq!(Lifecycle::from(value), lifecycle());
+    }
+
+    #[test]
+    fn test_lifecycle_configuration_serialize() {
+        let value = super::LifecycleConfiguration::from(lifecycle());
+        assert_eq!(quick_xml::se::to_string(&value).unwrap(), DOCUMENT);
+    }
+
+    #[test]
+    fn test_lifecycle_json() {
+        let value = serde_json::json!({
+            "rule": [
+                {
+                    "action": {"type": "SetStorageClass", "storageClass": "NEARLINE"},
+                    "condition": {"age": 365, "matchesStorageClass": ["MULTI_REGIONAL", "STANDARD"]},
+                },
+                {
+                    "action": {"type": "Delete"},
+                    "condition": {"isLive": false, "matchesPrefix": ["logs/"], "numNewerVersions": 3},
+                },
+                {
+                    "action": {"type": "AbortIncompleteMultipartUpload"},
+                    "condition": {"age": 7},
+                },
+            ],
+        });
+        assert_eq!(serde_json::to_value(lifecycle()).unwrap(), value);
+        assert_eq!(
+            serde_json::from_value::<Lifecycle>(value).unwrap(),
+            lifecycle()
+        );
+    }
+}
@@ -0,0 +1,256 @@
+// https://cloud.google.com/storage/docs/xml-api/get-bucket-list
+
+use http::{Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B>(bucket_name: B) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        delimiter: None,
+        marker: None,
+        max_keys: None,
+        prefix: None,
+        versions: false,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    delimiter: Option<String>,
+    marker: Option<String>,
+    max_keys: Option<u32>,
+    prefix: Option<String>,
+    versions: bool,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: Default,
+        U: Body,
+    {
+        let Self {
+            bucket_name,
+            delimiter,
+            marker,
+            max_keys,
+            prefix,
+            versions,
+        } = self;
+        let uri = super::query(
+            format!("https://{bucket_name}.storage.googleapis.com/"),
+            [
+                delimiter.map(|delimiter| ("delimiter", delimiter)),
+                marker.map(|marker| ("marker", marker)),
+                max_keys.map(|max_keys| ("max-keys", max_keys.to_string())),
+                prefix.map(|prefix| ("prefix", prefix)),
+                versions.then(|| ("versions", "true".to_owned())),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        super::from_xml(super::send(service, Request::get(uri), T::default()))
+    }
+}
+pub type Future<S, T, U> = super::FromXml<super::Send<S, T, U>, U, ListBucketResult>;
+
+impl Builder {
+    pub fn delimiter<D>(mut self, delimiter: D) -> Self
+    where
+        D: Into<String>,
+    {
+        self.delimiter = Some(delimiter.into());
+        self
+    }
+
+    pub fn marker<M>(mut self, marker: M) -> Self
+    where
+        M: Into<String>,
+    {
+        self.marker = Some(marker.into());
+        self
+    }
+
+    pub fn max_keys(mut self, max_keys: u32) -> Self {
+        self.max_keys = Some(max_keys);
+        self
+    }
+
+    pub fn prefix<P>(mut self, prefix: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn versions(mut self, versions: bool) -> Self {
+        self.versions = versions;
+        self
+    }
+}
+
+// the root element is `ListVersionsResult` instead of `ListBucketResult` when `versions` is set
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListBucketResult {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_marker: Option<String>,
+    #[serde(default)]
+    pub is_truncated: bool,
+    #[serde(default)]
+    pub contents: Vec<Contents>,
+    #[serde(default)]
+    pub version: Vec<Contents>,
+    #[serde(default)]
+    pub delete_marker: Vec<DeleteMarker>,
+    #[serde(default)]
+    pub common_prefixes: Vec<CommonPrefixes>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Contents {
+    pub key: String,
+    pub generation: u64,
+    pub meta_generation: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_latest: Option<bool>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_modified: time::OffsetDateTime,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteMarker {
+    pub key: String,
+    pub generation: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_latest: Option<bool>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_modified: time::OffsetDateTime,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CommonPrefixes {
+    pub prefix: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    #[test]
+    fn test_list_bucket_result() {
+        let document = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<ListBucketResult xmlns="http://doc.s3.amazonaws.com/2006-03-01">"#,
+            "<Name>travel-maps</Name>",
+            "<Prefix>europe/</Prefix>",
+            "<Marker></Marker>",
+            "<IsTruncated>false</IsTruncated>",
+            "<Contents>",
+            "<Key>europe/finland.jpg</Key>",
+            "<Generation>1360887697105000</Generation>",
+            "<MetaGeneration>1</MetaGeneration>",
+            "<LastModified>2013-02-15T00:21:37.105Z</LastModified>",
+            "<ETag>\"1f3cd8f4b8e1c9b5fe2a5c41e9e8df27\"</ETag>",
+            "<Size>317</Size>",
+            "</Contents>",
+            "<CommonPrefixes><Prefix>europe/france/</Prefix></CommonPrefixes>",
+            "</ListBucketResult>",
+        );
+        let value = quick_xml::de::from_str::<super::ListBucketResult>(document).unwrap();
+        assert_eq!(value.name, "travel-maps");
+        assert_eq!(value.prefix.as_deref(), Some("europe/"));
+        assert!(!value.is_truncated);
+        assert_eq!(
+            value.contents,
+            [super::Contents {
+                key: "europe/finland.jpg".to_owned(),
+                generation: 1360887697105000,
+                meta_generation: 1,
+                is_latest: None,
+                last_modified: datetime!(2013-02-15 00:21:37.105 UTC),
+                etag: "\"1f3cd8f4b8e1c9b5fe2a5c41e9e8df27\"".to_owned(),
+                size: 317,
+            }],
+        );
+        assert_eq!(
+            value.common_prefixes,
+            [super::CommonPrefixes {
+                prefix: "europe/france/".to_owned(),
+            }],
+        );
+    }
+
+    #[test]
+    fn test_list_versions_result() {
+        let document = concat!(
+            "<ListVersionsResult>",
+            "<Name>travel-maps</Name>",
+            "<IsTruncated>false</IsTruncated>",
+            "<Version>",
+            "<Key>paris.jpg</Key>",
+            "<Generation>1360887759327000</Generation>",
+            "<MetaGeneration>1</MetaGeneration>",
+            "<IsLatest>true</IsLatest>",
+            "<LastModified>2013-02-15T00:22:39.327Z</LastModified>",
+            "<ETag>\"e8ee9d5fcb7d4bd9b7cb58ee6a1b5ff4\"</ETag>",
+            "<Size>1024</Size>",
+            "</Version>",
+            "<DeleteMarker>",
+            "<Key>rome.jpg</Key>",
+            "<Generation>1360887697105000</Generation>",
+            "<IsLatest>true</IsLatest>",
+            "<LastModified>2013-02-15T00:21:37.105Z</LastModified>",
+            "</DeleteMarker>",
+            "<Version>",
+            "<Key>paris.jpg</Key>",
+            "<Generation>1360887697105000</Generation>",
+            "<MetaGeneration>2</MetaGeneration>",
+            "<IsLatest>false</IsLatest>",
+            "<LastModified>2013-02-15T00:21:37.105Z</LastModified>",
+            "<ETag>\"1f3cd8f4b8e1c9b5fe2a5c41e9e8df27\"</ETag>",
+            "<Size>317</Size>",
+            "</Version>",
+            "</ListVersionsResult>",
+        );
+        let value = quick_xml::de::from_str::<super::ListBucketResult>(document).unwrap();
+        assert!(value.contents.is_empty());
+        assert_eq!(
+            value
+                .version
+                .iter()
+                .map(|version| (version.generation, version.is_latest))
+                .collect::<Vec<_>>(),
+            [
+                (1360887759327000, Some(true)),
+                (1360887697105000, Some(false))
+            ],
+        );
+        assert_eq!(
+            value.delete_marker,
+            [super::DeleteMarker {
+                key: "rome.jpg".to_owned(),
+                generation: 1360887697105000,
+                is_latest: Some(true),
+                last_modified: datetime!(2013-02-15 00:21:37.105 UTC),
+            }],
+        );
+    }
+}
@@ -0,0 +1,41 @@
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-acls
+
+use super::access_control_list::AccessControlList;
+use http::{Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B>(bucket_name: B, access_control_list: AccessControlList) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        access_control_list,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    access_control_list: AccessControlList,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: From<String>,
+        U: Body,
+    {
+        let Self {
+            bucket_name,
+            access_control_list,
+        } = self;
+        super::empty(super::send_xml(
+            service,
+            Request::put(super::bucket_uri(bucket_name, "acl")),
+            access_control_list,
+        ))
+    }
+}
+pub type Future<S, T, U> = super::Empty<super::SendXml<S, T, U>, U>;
@@ -0,0 +1,39 @@
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-cors
+
+use super::cors_config::CorsConfig;
+use crate::bucket::Cors;
+use http::{Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B>(bucket_name: B, cors: Vec<Cors>) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        cors,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    cors: Vec<Cors>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: From<String>,
+        U: Body,
+    {
+        let Self { bucket_name, cors } = self;
+        super::empty(super::send_xml(
+            service,
+            Request::put(super::bucket_uri(bucket_name, "cors")),
+            CorsConfig::from(cors),
+        ))
+    }
+}
+pub type Future<S, T, U> = super::Empty<super::SendXml<S, T, U>, U>;
@@ -0,0 +1,42 @@
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-lifecycle
+
+use super::lifecycle_configuration::LifecycleConfiguration;
+use crate::bucket::Lifecycle;
+use http::{Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B>(bucket_name: B, lifecycle: Lifecycle) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        lifecycle,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    lifecycle: Lifecycle,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: From<String>,
+        U: Body,
+    {
+        let Self {
+            bucket_name,
+            lifecycle,
+        } = self;
+        super::empty(super::send_xml(
+            service,
+            Request::put(super::bucket_uri(bucket_name, "lifecycle")),
+            LifecycleConfiguration::from(lifecycle),
+        ))
+    }
+}
+pub type Future<S, T, U> = super::Empty<super::SendXml<S, T, U>, U>;
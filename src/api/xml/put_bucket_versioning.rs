@@ -0,0 +1,42 @@
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-versioning
+
+use super::versioning_configuration::VersioningConfiguration;
+use crate::bucket::Versioning;
+use http::{Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B>(bucket_name: B, versioning: Versioning) -> Builder
+where
+    B: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        versioning,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    versioning: Versioning,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: From<String>,
+        U: Body,
+    {
+        let Self {
+            bucket_name,
+            versioning,
+        } = self;
+        super::empty(super::send_xml(
+            service,
+            Request::put(super::bucket_uri(bucket_name, "versioning")),
+            VersioningConfiguration::from(versioning),
+        ))
+    }
+}
+pub type Future<S, T, U> = super::Empty<super::SendXml<S, T, U>, U>;
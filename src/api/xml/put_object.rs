@@ -1,5 +1,6 @@
 // https://cloud.google.com/storage/docs/xml-api/put-object-upload
 
+use crate::encryption::CustomerEncryptionKey;
 use headers::{Header, HeaderMapExt};
 use http::{HeaderMap, Request, Response};
 use http_body::Body;
@@ -14,6 +15,7 @@ where
         bucket_name: bucket_name.into(),
         object_name: object_name.into(),
         body,
+        customer_encryption_key: None,
         headers: HeaderMap::new(),
     }
 }
@@ -22,6 +24,7 @@ pub struct Builder<T> {
     bucket_name: String,
     object_name: String,
     body: T,
+    customer_encryption_key: Option<CustomerEncryptionKey>,
     headers: HeaderMap,
 }
 
@@ -35,18 +38,30 @@ impl<T> Builder<T> {
             bucket_name,
             object_name,
             body,
+            customer_encryption_key,
             headers,
         } = self;
         let mut builder = Request::put(super::uri(bucket_name, object_name));
         if let Some(h) = builder.headers_mut() {
             *h = headers;
         }
+        let builder = crate::encryption::with_headers(
+            builder,
+            customer_encryption_key
+                .iter()
+                .flat_map(CustomerEncryptionKey::headers),
+        );
         super::empty(super::send(service, builder, body))
     }
 }
 pub type Future<S, T, U> = super::Empty<super::Send<S, T, U>, U>;
 
 impl<T> Builder<T> {
+    pub fn customer_encryption_key(mut self, key: CustomerEncryptionKey) -> Self {
+        self.customer_encryption_key = Some(key);
+        self
+    }
+
     pub fn typed_header<H>(mut self, header: H) -> Self
     where
         H: Header,
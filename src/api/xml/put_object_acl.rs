@@ -0,0 +1,63 @@
+// https://cloud.google.com/storage/docs/xml-api/put-object-acls
+
+use super::access_control_list::AccessControlList;
+use http::{Request, Response};
+use http_body::Body;
+use tower::Service;
+
+pub fn builder<B, O>(
+    bucket_name: B,
+    object_name: O,
+    access_control_list: AccessControlList,
+) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        object_name: object_name.into(),
+        access_control_list,
+        generation: None,
+    }
+}
+
+pub struct Builder {
+    bucket_name: String,
+    object_name: String,
+    access_control_list: AccessControlList,
+    generation: Option<u64>,
+}
+
+impl Builder {
+    pub fn send<S, T, U>(self, service: S) -> Future<S, T, U>
+    where
+        S: Service<Request<T>, Response = Response<U>>,
+        T: From<String>,
+        U: Body,
+    {
+        let Self {
+            bucket_name,
+            object_name,
+            access_control_list,
+            generation,
+        } = self;
+        let uri = super::query(
+            format!("{}?acl", super::uri(bucket_name, object_name)),
+            generation.map(|generation| ("generation", generation.to_string())),
+        );
+        super::empty(super::send_xml(
+            service,
+            Request::put(uri),
+            access_control_list,
+        ))
+    }
+}
+pub type Future<S, T, U> = super::Empty<super::SendXml<S, T, U>, U>;
+
+impl Builder {
+    pub fn generation(mut self, generation: u64) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+}
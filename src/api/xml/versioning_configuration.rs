@@ -0,0 +1,77 @@
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-versioning#request_body_elements
+
+use crate::bucket;
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename = "VersioningConfiguration", rename_all = "PascalCase")]
+pub struct VersioningConfiguration {
+    // absent if versioning has never been enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<Status>,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+enum Status {
+    Enabled,
+    Suspended,
+}
+
+impl From<bucket::Versioning> for VersioningConfiguration {
+    fn from(value: bucket::Versioning) -> Self {
+        Self {
+            status: Some(if value.enabled {
+                Status::Enabled
+            } else {
+                Status::Suspended
+            }),
+        }
+    }
+}
+
+impl From<VersioningConfiguration> for bucket::Versioning {
+    fn from(value: VersioningConfiguration) -> Self {
+        Self {
+            enabled: value.status == Some(Status::Enabled),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bucket::Versioning;
+
+    #[test]
+    fn test_versioning_configuration() {
+        for (document, versioning) in [
+            (
+                "<VersioningConfiguration><Status>Enabled</Status></VersioningConfiguration>",
+                Versioning { enabled: true },
+            ),
+            (
+                "<VersioningConfiguration><Status>Suspended</Status></VersioningConfiguration>",
+                Versioning { enabled: false },
+            ),
+        ] {
+            assert_eq!(
+                Versioning::from(
+                    quick_xml::de::from_str::<super::VersioningConfiguration>(document).unwrap()
+                ),
+                versioning,
+            );
+            assert_eq!(
+                quick_xml::se::to_string(&super::VersioningConfiguration::from(versioning))
+                    .unwrap(),
+                document,
+            );
+        }
+        assert_eq!(
+            Versioning::from(
+                quick_xml::de::from_str::<super::VersioningConfiguration>(
+                    "<VersioningConfiguration/>"
+                )
+                .unwrap()
+            ),
+            Versioning { enabled: false },
+        );
+    }
+}
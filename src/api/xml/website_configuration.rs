@@ -0,0 +1,74 @@
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-websiteconfig#request_body_elements
+
+use crate::bucket;
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename = "WebsiteConfiguration", rename_all = "PascalCase")]
+pub struct WebsiteConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    main_page_suffix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    not_found_page: Option<String>,
+}
+
+impl From<bucket::Website> for WebsiteConfiguration {
+    fn from(value: bucket::Website) -> Self {
+        let bucket::Website {
+            main_page_suffix,
+            not_found_page,
+        } = value;
+        Self {
+            main_page_suffix,
+            not_found_page,
+        }
+    }
+}
+
+impl From<WebsiteConfiguration> for bucket::Website {
+    fn from(value: WebsiteConfiguration) -> Self {
+        let WebsiteConfiguration {
+            main_page_suffix,
+            not_found_page,
+        } = value;
+        Self {
+            main_page_suffix,
+            not_found_page,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bucket::Website;
+
+    #[test]
+    fn test_website_configuration() {
+        let document = concat!(
+            "<WebsiteConfiguration>",
+            "<MainPageSuffix>index.html</MainPageSuffix>",
+            "<NotFoundPage>404.html</NotFoundPage>",
+            "</WebsiteConfiguration>",
+        );
+        let website = Website {
+            main_page_suffix: Some("index.html".to_owned()),
+            not_found_page: Some("404.html".to_owned()),
+        };
+        assert_eq!(
+            Website::from(
+                quick_xml::de::from_str::<super::WebsiteConfiguration>(document).unwrap()
+            ),
+            website,
+        );
+        assert_eq!(
+            quick_xml::se::to_string(&super::WebsiteConfiguration::from(website)).unwrap(),
+            document,
+        );
+        assert_eq!(
+            Website::from(
+                quick_xml::de::from_str::<super::WebsiteConfiguration>("<WebsiteConfiguration/>")
+                    .unwrap()
+            ),
+            Website::default(),
+        );
+    }
+}
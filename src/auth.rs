@@ -0,0 +1,646 @@
+// attaches OAuth 2.0 access tokens from a TokenProvider to every request
+
+pub mod impersonated;
+pub mod metadata;
+
+use crate::retry::Transient;
+use futures::future::{Either, Shared};
+use futures::FutureExt;
+use headers::authorization::InvalidBearerToken;
+use headers::{Authorization, HeaderMapExt};
+use http::Request;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::{self, Ready};
+use std::mem;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+
+pub const CLOUD_PLATFORM: &str = "https://www.googleapis.com/auth/cloud-platform";
+pub const DEVSTORAGE_FULL_CONTROL: &str = "https://www.googleapis.com/auth/devstorage.full_control";
+pub const DEVSTORAGE_READ_ONLY: &str = "https://www.googleapis.com/auth/devstorage.read_only";
+pub const DEVSTORAGE_READ_WRITE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
+
+// a source of access tokens, e.g. the yup-oauth2 authenticator, a credential broker or a fixed
+// Token in tests
+pub trait TokenProvider {
+    type Error;
+    type Future: future::Future<Output = Result<Token, Self::Error>>;
+
+    fn token(&self, scopes: &[String]) -> Self::Future;
+}
+
+// a request extension that overrides the scopes of the Layer for that request, e.g.
+//
+// let mut request = http::Request::get(uri).body(body)?;
+// request.extensions_mut().insert(auth::Scopes::new([auth::DEVSTORAGE_READ_ONLY]));
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scopes(pub Vec<String>);
+
+impl Scopes {
+    pub fn new<I>(scopes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self(scopes.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub access_token: String,
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+impl Token {
+    pub fn new<A>(access_token: A) -> Self
+    where
+        A: Into<String>,
+    {
+        Self {
+            access_token: access_token.into(),
+            expires_at: None,
+        }
+    }
+}
+
+// a token is its own provider
+impl TokenProvider for Token {
+    type Error = Infallible;
+    type Future = Ready<Result<Token, Self::Error>>;
+
+    fn token(&self, _: &[String]) -> Self::Future {
+        future::ready(Ok(self.clone()))
+    }
+}
+
+// reuses the token of a provider for the same scopes until margin before it expires. tokens
+// without an expiry are kept until the Cached (and its clones) are dropped.
+//
+// concurrent callers share a single refresh, and while a token that is within the margin but not
+// yet expired is being refreshed, everyone but the caller that started the refresh keeps getting
+// it. a refresh that fails with a transient error is returned to every caller for failure_ttl
+// before the provider is asked again; other failures are not cached.
+//
+// a refresh that has not finished after refresh_timeout is abandoned and the next caller starts a
+// new one. callers already waiting for the abandoned refresh keep waiting for it, so bound those
+// with a timeout around the service.
+pub struct Cached<P>
+where
+    P: TokenProvider,
+{
+    provider: P,
+    margin: Duration,
+    failure_ttl: Duration,
+    refresh_timeout: Duration,
+    entries: Arc<Entries<P::Future, P::Error>>,
+    counters: Arc<Counters>,
+}
+
+type Entries<F, E> = Mutex<HashMap<Vec<String>, Entry<F, E>>>;
+
+struct Entry<F, E>
+where
+    F: future::Future<Output = Result<Token, E>>,
+{
+    token: Option<Token>,
+    failure: Option<(Arc<E>, Instant)>,
+    refresh: Option<InFlight<F, E>>,
+}
+
+struct InFlight<F, E>
+where
+    F: future::Future<Output = Result<Token, E>>,
+{
+    id: u64,
+    started_at: Instant,
+    refresh: Shared<Refresh<F, E>>,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    coalesced: AtomicU64,
+    refreshes: AtomicU64,
+    failures: AtomicU64,
+    negative_hits: AtomicU64,
+    abandoned: AtomicU64,
+}
+
+// counts since the Cached was created, shared by its clones
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    // tokens served from the cache
+    pub hits: u64,
+    // callers that waited for a refresh started by another caller
+    pub coalesced: u64,
+    // calls to the provider
+    pub refreshes: u64,
+    // calls to the provider that failed
+    pub failures: u64,
+    // failures served from the cache
+    pub negative_hits: u64,
+    // refreshes that did not finish within refresh_timeout
+    pub abandoned: u64,
+}
+
+impl<P> Clone for Cached<P>
+where
+    P: Clone + TokenProvider,
+{
+    fn clone(&self) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            margin: self.margin,
+            failure_ttl: self.failure_ttl,
+            refresh_timeout: self.refresh_timeout,
+            entries: self.entries.clone(),
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+impl<P> Cached<P>
+where
+    P: TokenProvider,
+{
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            margin: Duration::from_secs(4 * 60),
+            failure_ttl: Duration::from_secs(5),
+            refresh_timeout: Duration::from_secs(30),
+            entries: Arc::default(),
+            counters: Arc::default(),
+        }
+    }
+
+    // how long before expiry a token is refreshed, 4 minutes by default
+    pub fn margin(mut self, margin: Duration) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    // how long a failed refresh is returned before the provider is asked again, 5 seconds by
+    // default. Duration::ZERO disables negative caching.
+    pub fn failure_ttl(mut self, failure_ttl: Duration) -> Self {
+        self.failure_ttl = failure_ttl;
+        self
+    }
+
+    // how long a refresh may take before the next caller starts another one, 30 seconds by default
+    pub fn refresh_timeout(mut self, refresh_timeout: Duration) -> Self {
+        self.refresh_timeout = refresh_timeout;
+        self
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        let Counters {
+            hits,
+            coalesced,
+            refreshes,
+            failures,
+            negative_hits,
+            abandoned,
+        } = &*self.counters;
+        Metrics {
+            hits: hits.load(Ordering::Relaxed),
+            coalesced: coalesced.load(Ordering::Relaxed),
+            refreshes: refreshes.load(Ordering::Relaxed),
+            failures: failures.load(Ordering::Relaxed),
+            negative_hits: negative_hits.load(Ordering::Relaxed),
+            abandoned: abandoned.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<P> TokenProvider for Cached<P>
+where
+    P: TokenProvider,
+    P::Error: Transient,
+{
+    type Error = Arc<P::Error>;
+    type Future = Either<Ready<Result<Token, Self::Error>>, Shared<Refresh<P::Future, P::Error>>>;
+
+    fn token(&self, scopes: &[String]) -> Self::Future {
+        let now = OffsetDateTime::now_utc();
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry(scopes.to_vec()).or_insert_with(|| Entry {
+            token: None,
+            failure: None,
+            refresh: None,
+        });
+        if entry
+            .refresh
+            .as_ref()
+            .is_some_and(|in_flight| in_flight.started_at.elapsed() >= self.refresh_timeout)
+        {
+            self.counters.abandoned.fetch_add(1, Ordering::Relaxed);
+            entry.refresh = None;
+        }
+
+        let fresh = |margin| {
+            entry.token.as_ref().filter(|token| {
+                token
+                    .expires_at
+                    .is_none_or(|expires_at| now + margin < expires_at)
+            })
+        };
+        let failure = entry
+            .failure
+            .as_ref()
+            .filter(|(_, failed_at)| failed_at.elapsed() < self.failure_ttl);
+        let token = match (&entry.refresh, failure) {
+            (None, None) => fresh(self.margin),
+            // a refresh is in flight or has just failed; an unexpired token is still good
+            _ => fresh(Duration::ZERO),
+        };
+        if let Some(token) = token {
+            self.counters.hits.fetch_add(1, Ordering::Relaxed);
+            return Either::Left(future::ready(Ok(token.clone())));
+        }
+        if let Some(in_flight) = &entry.refresh {
+            self.counters.coalesced.fetch_add(1, Ordering::Relaxed);
+            return Either::Right(in_flight.refresh.clone());
+        }
+        if let Some((e, _)) = failure {
+            self.counters.negative_hits.fetch_add(1, Ordering::Relaxed);
+            return Either::Left(future::ready(Err(e.clone())));
+        }
+
+        let id = self.counters.refreshes.fetch_add(1, Ordering::Relaxed);
+        let refresh = Refresh {
+            f: self.provider.token(scopes),
+            id,
+            is_transient: P::Error::is_transient,
+            scopes: scopes.to_vec(),
+            entries: Arc::downgrade(&self.entries),
+            counters: self.counters.clone(),
+        }
+        .shared();
+        entry.refresh = Some(InFlight {
+            id,
+            started_at: Instant::now(),
+            refresh: refresh.clone(),
+        });
+        Either::Right(refresh)
+    }
+}
+
+// stores the result of the provider in the Cached it was started from. whoever polls the Shared
+// first drives it. an abandoned refresh still stores its token but leaves the entry otherwise alone.
+#[pin_project::pin_project]
+pub struct Refresh<F, E>
+where
+    F: future::Future<Output = Result<Token, E>>,
+{
+    #[pin]
+    f: F,
+    id: u64,
+    // Transient::is_transient of the provider's errors, which decides what is negatively cached
+    is_transient: fn(&E) -> bool,
+    scopes: Vec<String>,
+    entries: Weak<Entries<F, E>>,
+    counters: Arc<Counters>,
+}
+
+impl<F, E> future::Future for Refresh<F, E>
+where
+    F: future::Future<Output = Result<Token, E>>,
+{
+    type Output = Result<Token, Arc<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let output = ready!(this.f.poll(cx)).map_err(Arc::new);
+        if output.is_err() {
+            this.counters.failures.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(entries) = this.entries.upgrade() {
+            let mut entries = entries.lock().unwrap();
+            if let Some(entry) = entries.get_mut(&*this.scopes) {
+                let current = entry
+                    .refresh
+                    .as_ref()
+                    .is_some_and(|in_flight| in_flight.id == *this.id);
+                if current {
+                    entry.refresh = None;
+                }
+                match &output {
+                    Ok(token) => {
+                        entry.token = Some(token.clone());
+                        entry.failure = None;
+                    }
+                    Err(e) if current && (this.is_transient)(e) => {
+                        entry.failure = Some((e.clone(), Instant::now()));
+                    }
+                    Err(_) => (),
+                }
+            }
+        }
+        Poll::Ready(output)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<P, S> {
+    #[error(transparent)]
+    InvalidBearerToken(InvalidBearerToken),
+    #[error(transparent)]
+    Service(S),
+    #[error(transparent)]
+    TokenProvider(P),
+}
+
+#[derive(Clone)]
+pub struct Service<S, P> {
+    inner: S,
+    provider: P,
+    scopes: Vec<String>,
+}
+
+impl<S, P, B> tower::Service<Request<B>> for Service<S, P>
+where
+    S: Clone + tower::Service<Request<B>>,
+    P: TokenProvider,
+{
+    type Response = S::Response;
+    type Error = Error<P::Error, S::Error>;
+    type Future = Future<S, P, B>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Error::Service)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let inner = self.inner.clone();
+        let inner = mem::replace(&mut self.inner, inner);
+        let scopes = match request.extensions().get::<Scopes>() {
+            Some(Scopes(scopes)) => scopes,
+            None => &self.scopes,
+        };
+        Future {
+            state: State::S0 {
+                f: self.provider.token(scopes),
+            },
+            inner,
+            request: Some(request),
+        }
+    }
+}
+
+#[pin_project::pin_project]
+pub struct Future<S, P, B>
+where
+    S: tower::Service<Request<B>>,
+    P: TokenProvider,
+{
+    #[pin]
+    state: State<P::Future, S::Future>,
+    inner: S,
+    request: Option<Request<B>>,
+}
+
+#[pin_project::pin_project(project = StateProj)]
+enum State<F, G> {
+    S0 {
+        #[pin]
+        f: F,
+    },
+    S1 {
+        #[pin]
+        f: G,
+    },
+}
+
+impl<S, P, B> future::Future for Future<S, P, B>
+where
+    S: tower::Service<Request<B>>,
+    P: TokenProvider,
+{
+    type Output = Result<S::Response, Error<P::Error, S::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            match this.state.as_mut().project() {
+                StateProj::S0 { f } => {
+                    let token = ready!(f.poll(cx)).map_err(Error::TokenProvider)?;
+                    let header = Authorization::bearer(&token.access_token)
+                        .map_err(Error::InvalidBearerToken)?;
+                    let mut request = this.request.take().unwrap();
+                    request.headers_mut().typed_insert(header);
+                    let f = this.inner.call(request);
+                    this.state.set(State::S1 { f });
+                }
+                StateProj::S1 { f } => {
+                    let response = ready!(f.poll(cx)).map_err(Error::Service)?;
+                    break Poll::Ready(Ok(response));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Layer<P> {
+    provider: P,
+    scopes: Vec<String>,
+}
+
+impl<S, P> tower::Layer<S> for Layer<P>
+where
+    P: Clone,
+{
+    type Service = Service<S, P>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Service {
+            inner,
+            provider: self.provider.clone(),
+            scopes: self.scopes.clone(),
+        }
+    }
+}
+
+impl<P> Layer<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            scopes: vec![CLOUD_PLATFORM.to_owned()],
+        }
+    }
+
+    // e.g. to read the Metrics of a Cached provider
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    // e.g. to set the margin of a Cached provider
+    pub fn map_provider<F, Q>(self, f: F) -> Layer<Q>
+    where
+        F: FnOnce(P) -> Q,
+    {
+        Layer {
+            provider: f(self.provider),
+            scopes: self.scopes,
+        }
+    }
+
+    // the scopes of requests without a Scopes extension, cloud-platform by default
+    pub fn scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cached, Metrics, Token, TokenProvider};
+    use crate::mock::{Expectation, Service};
+    use bytes::Bytes;
+    use futures::future::Either;
+    use http::Method;
+    use http_body_util::Full;
+    use std::convert::Infallible;
+    use std::future::{self, Pending, Ready};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tower::{Layer, ServiceExt};
+
+    // never answers the first call
+    #[derive(Clone, Default)]
+    struct Stuck(Arc<AtomicUsize>);
+
+    impl TokenProvider for Stuck {
+        type Error = Infallible;
+        type Future = Either<Pending<Result<Token, Infallible>>, Ready<Result<Token, Infallible>>>;
+
+        fn token(&self, _: &[String]) -> Self::Future {
+            if self.0.fetch_add(1, Ordering::Relaxed) == 0 {
+                Either::Left(future::pending())
+            } else {
+                Either::Right(future::ready(Ok(Token::new("ya29.token"))))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_layer() {
+        let mock = Service::new();
+        mock.expect(
+            Expectation::new(Method::GET, "https://bucket.storage.googleapis.com/object")
+                .header(http::header::AUTHORIZATION, "Bearer ya29.token"),
+        );
+        let service = super::Layer::new(super::Token::new("ya29.token")).layer(mock.clone());
+        crate::api::xml::get_object::builder("bucket", "object")
+            .send::<_, Full<Bytes>, _>(service)
+            .await
+            .unwrap();
+        mock.assert_done();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_bearer_token() {
+        let mock = Service::new();
+        let service = super::Layer::new(super::Token::new("ya29.\n")).layer(mock.clone());
+        let e = crate::api::xml::get_object::builder("bucket", "object")
+            .send::<_, Full<Bytes>, _>(service)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            e,
+            crate::api::Error::Service(super::Error::InvalidBearerToken(_)),
+        ));
+        mock.assert_done();
+    }
+
+    #[tokio::test]
+    async fn test_scopes() {
+        let metadata = Service::new();
+        let mock = Service::new();
+        let provider =
+            super::metadata::MetadataServer::<_, String>::new(metadata.clone()).host("127.0.0.1");
+        let service = super::Layer::new(provider)
+            .scopes(vec![super::DEVSTORAGE_READ_ONLY.to_owned()])
+            .layer(mock.clone());
+        for (scopes, access_token) in [
+            ("devstorage%2Eread%5Fonly", "ya29.r"),
+            ("devstorage%2Efull%5Fcontrol", "ya29.f"),
+        ] {
+            metadata.expect(
+                Expectation::new(
+                    Method::GET,
+                    format!(
+                        "http://127.0.0.1/computeMetadata/v1/instance/service-accounts/default/token\
+                         ?scopes=https%3A%2F%2Fwww%2Egoogleapis%2Ecom%2Fauth%2F{scopes}"
+                    ),
+                )
+                .response_json(&serde_json::json!({
+                    "access_token": access_token,
+                    "expires_in": 3599,
+                    "token_type": "Bearer",
+                })),
+            );
+            mock.expect(
+                Expectation::new(Method::GET, "https://bucket.storage.googleapis.com/object")
+                    .header(
+                        http::header::AUTHORIZATION,
+                        format!("Bearer {access_token}"),
+                    ),
+            );
+        }
+
+        crate::api::xml::get_object::builder("bucket", "object")
+            .send::<_, Full<Bytes>, _>(service.clone())
+            .await
+            .unwrap();
+        // overridden for a single request
+        crate::api::xml::get_object::builder("bucket", "object")
+            .send::<_, Full<Bytes>, _>(service.map_request(|mut request: http::Request<_>| {
+                request
+                    .extensions_mut()
+                    .insert(super::Scopes::new([super::DEVSTORAGE_FULL_CONTROL]));
+                request
+            }))
+            .await
+            .unwrap();
+        metadata.assert_done();
+        mock.assert_done();
+    }
+
+    #[tokio::test]
+    async fn test_cached_refresh_timeout() {
+        let provider = Cached::new(Stuck::default()).refresh_timeout(Duration::from_millis(50));
+        tokio::time::timeout(Duration::from_millis(100), provider.token(&[]))
+            .await
+            .unwrap_err();
+        // the stuck refresh is abandoned instead of being shared with every later caller
+        assert_eq!(
+            provider.token(&[]).await.unwrap().access_token,
+            "ya29.token"
+        );
+        assert_eq!(
+            provider.token(&[]).await.unwrap().access_token,
+            "ya29.token"
+        );
+
+        assert_eq!(
+            provider.metrics(),
+            Metrics {
+                hits: 1,
+                coalesced: 0,
+                refreshes: 2,
+                failures: 0,
+                negative_hits: 0,
+                abandoned: 1,
+            },
+        );
+    }
+}
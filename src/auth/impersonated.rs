@@ -0,0 +1,141 @@
+// tokens of a target service account, minted through generateAccessToken with the base credential
+// of the service (e.g. a super::Service over the user's own credential), optionally through a
+// chain of delegates
+//
+// every call mints a new token; wrap in super::Cached to reuse tokens until shortly before they
+// expire.
+
+use super::{Token, TokenProvider};
+use crate::api::iam_credentials::generate_access_token;
+use futures::future::MapOk;
+use futures::TryFutureExt;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+pub struct Impersonated<S, T> {
+    service: S,
+    client_email: String,
+    delegates: Vec<String>,
+    lifetime: Option<Duration>,
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<S, T> Clone for Impersonated<S, T>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            client_email: self.client_email.clone(),
+            delegates: self.delegates.clone(),
+            lifetime: self.lifetime,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S, T> Impersonated<S, T> {
+    pub fn new<E>(service: S, client_email: E) -> Self
+    where
+        E: Into<String>,
+    {
+        Self {
+            service,
+            client_email: client_email.into(),
+            delegates: Vec::new(),
+            lifetime: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    // projects/-/serviceAccounts/{email}, each granted roles/iam.serviceAccountTokenCreator on
+    // the next one
+    pub fn delegates(mut self, delegates: Vec<String>) -> Self {
+        self.delegates = delegates;
+        self
+    }
+
+    pub fn lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = Some(lifetime);
+        self
+    }
+}
+
+impl<S, T, U> TokenProvider for Impersonated<S, T>
+where
+    S: Clone + tower::Service<http::Request<T>, Response = http::Response<U>>,
+    T: From<String>,
+    U: http_body::Body,
+{
+    type Error = crate::api::Error<S::Error, U::Error>;
+    type Future = MapOk<
+        generate_access_token::Future<S, T, U>,
+        fn(http::Response<generate_access_token::Response>) -> Token,
+    >;
+
+    fn token(&self, scopes: &[String]) -> Self::Future {
+        let mut builder =
+            generate_access_token::builder(self.client_email.clone(), scopes.to_vec())
+                .delegates(self.delegates.clone());
+        if let Some(lifetime) = self.lifetime {
+            builder = builder.lifetime(lifetime);
+        }
+        builder.send(self.service.clone()).map_ok(|response| {
+            let response = response.into_body();
+            Token {
+                access_token: response.access_token,
+                expires_at: Some(response.expire_time),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Layer, Token, TokenProvider};
+    use crate::mock::{Expectation, Service};
+    use http::Method;
+    use std::time::Duration;
+    use time::macros::datetime;
+    use tower::Layer as _;
+
+    #[tokio::test]
+    async fn test_token() {
+        let mock = Service::new();
+        mock.expect(
+            Expectation::new(
+                Method::POST,
+                "https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/deploy@p.iam.gserviceaccount.com:generateAccessToken",
+            )
+            .header(http::header::AUTHORIZATION, "Bearer ya29.user")
+            .body(concat!(
+                r#"{"delegates":["projects/-/serviceAccounts/ci@p.iam.gserviceaccount.com"],"#,
+                r#""lifetime":"600s","#,
+                r#""scope":["https://www.googleapis.com/auth/devstorage.read_write"]}"#,
+            ))
+            .response_json(&serde_json::json!({
+                "accessToken": "ya29.deploy",
+                "expireTime": "2025-01-01T00:10:00Z",
+            })),
+        );
+        let base = Layer::new(Token::new("ya29.user")).layer(mock.clone());
+        let provider =
+            super::Impersonated::<_, String>::new(base, "deploy@p.iam.gserviceaccount.com")
+                .delegates(vec![
+                    "projects/-/serviceAccounts/ci@p.iam.gserviceaccount.com".to_owned(),
+                ])
+                .lifetime(Duration::from_secs(600));
+        assert_eq!(
+            provider
+                .token(&[super::super::DEVSTORAGE_READ_WRITE.to_owned()])
+                .await
+                .unwrap(),
+            Token {
+                access_token: "ya29.deploy".to_owned(),
+                expires_at: Some(datetime!(2025-01-01 00:10:00 UTC)),
+            },
+        );
+        mock.assert_done();
+    }
+}
@@ -0,0 +1,268 @@
+// tokens of the service account attached to a GCE instance, GKE workload, Cloud Run service, ...
+//
+// every call asks the metadata server; wrap in super::Cached to reuse tokens until shortly before
+// they expire.
+
+use super::{Token, TokenProvider};
+use crate::api::metadata::get_token;
+use futures::future::MapOk;
+use futures::TryFutureExt;
+use std::marker::PhantomData;
+use time::{Duration, OffsetDateTime};
+
+pub struct MetadataServer<S, T> {
+    service: S,
+    builder: get_token::Builder,
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<S, T> Clone for MetadataServer<S, T>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            builder: self.builder.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S, T> MetadataServer<S, T> {
+    pub fn new(service: S) -> Self {
+        Self {
+            service,
+            builder: get_token::builder(),
+            _phantom: PhantomData,
+        }
+    }
+
+    // host[:port], defaults to GCE_METADATA_HOST or metadata.google.internal
+    pub fn host<H>(mut self, host: H) -> Self
+    where
+        H: Into<String>,
+    {
+        self.builder = self.builder.host(host);
+        self
+    }
+
+    // an email or "default"
+    pub fn service_account<A>(mut self, service_account: A) -> Self
+    where
+        A: Into<String>,
+    {
+        self.builder = self.builder.service_account(service_account);
+        self
+    }
+}
+
+impl<S, T, U> TokenProvider for MetadataServer<S, T>
+where
+    S: Clone + tower::Service<http::Request<T>, Response = http::Response<U>>,
+    T: From<String>,
+    U: http_body::Body,
+{
+    type Error = crate::api::Error<S::Error, U::Error>;
+    type Future =
+        MapOk<get_token::Future<S, T, U>, fn(http::Response<get_token::Response>) -> Token>;
+
+    // no scopes means the scopes of the instance
+    fn token(&self, scopes: &[String]) -> Self::Future {
+        self.builder
+            .clone()
+            .scopes(scopes.to_vec())
+            .send(self.service.clone())
+            .map_ok(|response| {
+                let response = response.into_body();
+                Token {
+                    access_token: response.access_token,
+                    expires_at: Some(
+                        OffsetDateTime::now_utc() + Duration::seconds(response.expires_in),
+                    ),
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Cached, Metrics, TokenProvider};
+    use crate::mock::{Error, Expectation, Service};
+    use http::{Method, StatusCode};
+    use std::time::Duration;
+    use time::OffsetDateTime;
+
+    fn expect_token(mock: &Service, uri: &str, access_token: &str, expires_in: i64) {
+        mock.expect(
+            Expectation::new(Method::GET, uri)
+                .header("metadata-flavor", "Google")
+                .response_json(&serde_json::json!({
+                    "access_token": access_token,
+                    "expires_in": expires_in,
+                    "token_type": "Bearer",
+                })),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token() {
+        let mock = Service::new();
+        expect_token(
+            &mock,
+            "http://127.0.0.1:8080/computeMetadata/v1/instance/service-accounts/sa@p.iam.gserviceaccount.com/token\
+             ?scopes=https%3A%2F%2Fwww%2Egoogleapis%2Ecom%2Fauth%2Fdevstorage%2Eread%5Fonly",
+            "ya29.a",
+            3599,
+        );
+        let provider = super::MetadataServer::<_, String>::new(mock.clone())
+            .host("127.0.0.1:8080")
+            .service_account("sa@p.iam.gserviceaccount.com");
+        let token = provider
+            .token(&[super::super::DEVSTORAGE_READ_ONLY.to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "ya29.a");
+        let expires_in = token.expires_at.unwrap() - OffsetDateTime::now_utc();
+        assert!(expires_in > Duration::from_secs(3590) && expires_in <= Duration::from_secs(3599));
+        mock.assert_done();
+    }
+
+    #[tokio::test]
+    async fn test_cached() {
+        let mock = Service::new();
+        let uri =
+            "http://127.0.0.1:8080/computeMetadata/v1/instance/service-accounts/default/token";
+        expect_token(&mock, uri, "ya29.a", 3599);
+        let provider = Cached::new(
+            super::MetadataServer::<_, String>::new(mock.clone()).host("127.0.0.1:8080"),
+        );
+        assert_eq!(provider.token(&[]).await.unwrap().access_token, "ya29.a");
+        assert_eq!(
+            provider.clone().token(&[]).await.unwrap().access_token,
+            "ya29.a"
+        );
+        mock.assert_done();
+
+        // tokens are cached per scopes
+        let scopes = [super::super::DEVSTORAGE_READ_ONLY.to_owned()];
+        expect_token(
+            &mock,
+            &format!("{uri}?scopes=https%3A%2F%2Fwww%2Egoogleapis%2Ecom%2Fauth%2Fdevstorage%2Eread%5Fonly"),
+            "ya29.r",
+            3599,
+        );
+        assert_eq!(
+            provider.token(&scopes).await.unwrap().access_token,
+            "ya29.r"
+        );
+        assert_eq!(
+            provider.token(&scopes).await.unwrap().access_token,
+            "ya29.r"
+        );
+        assert_eq!(provider.token(&[]).await.unwrap().access_token, "ya29.a");
+        mock.assert_done();
+
+        // refreshed once within the margin
+        let provider = provider.margin(Duration::from_secs(60 * 60));
+        expect_token(&mock, uri, "ya29.b", 3599);
+        assert_eq!(provider.token(&[]).await.unwrap().access_token, "ya29.b");
+        mock.assert_done();
+    }
+
+    #[tokio::test]
+    async fn test_cached_single_flight() {
+        let mock = Service::new();
+        let uri =
+            "http://127.0.0.1:8080/computeMetadata/v1/instance/service-accounts/default/token";
+        expect_token(&mock, uri, "ya29.a", 3599);
+        let provider = Cached::new(
+            super::MetadataServer::<_, String>::new(mock.clone()).host("127.0.0.1:8080"),
+        );
+        let (a, b) = tokio::join!(provider.token(&[]), provider.clone().token(&[]));
+        assert_eq!(a.unwrap().access_token, "ya29.a");
+        assert_eq!(b.unwrap().access_token, "ya29.a");
+        mock.assert_done();
+
+        // within the margin, only the caller that starts the refresh waits for it
+        let provider = provider.margin(Duration::from_secs(60 * 60));
+        expect_token(&mock, uri, "ya29.b", 3599);
+        let (a, b) = tokio::join!(provider.token(&[]), provider.token(&[]));
+        assert_eq!(a.unwrap().access_token, "ya29.b");
+        assert_eq!(b.unwrap().access_token, "ya29.a");
+        mock.assert_done();
+
+        assert_eq!(
+            provider.metrics(),
+            Metrics {
+                hits: 1,
+                coalesced: 1,
+                refreshes: 2,
+                failures: 0,
+                negative_hits: 0,
+                abandoned: 0,
+            },
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cached_failure() {
+        let mock = Service::new();
+        let uri =
+            "http://127.0.0.1:8080/computeMetadata/v1/instance/service-accounts/default/token";
+        mock.expect(Expectation::new(Method::GET, uri).error(Error("timed out".to_owned())));
+        let provider = Cached::new(
+            super::MetadataServer::<_, String>::new(mock.clone()).host("127.0.0.1:8080"),
+        );
+        let a = provider.token(&[]).await.unwrap_err();
+        let b = provider.token(&[]).await.unwrap_err();
+        assert!(std::sync::Arc::ptr_eq(&a, &b));
+        mock.assert_done();
+
+        // asked again once the failure expires
+        let provider = provider.failure_ttl(Duration::ZERO);
+        expect_token(&mock, uri, "ya29.a", 3599);
+        assert_eq!(provider.token(&[]).await.unwrap().access_token, "ya29.a");
+        mock.assert_done();
+
+        assert_eq!(
+            provider.metrics(),
+            Metrics {
+                hits: 0,
+                coalesced: 0,
+                refreshes: 2,
+                failures: 1,
+                negative_hits: 1,
+                abandoned: 0,
+            },
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cached_permanent_failure() {
+        let mock = Service::new();
+        let uri =
+            "http://127.0.0.1:8080/computeMetadata/v1/instance/service-accounts/default/token";
+        let provider = Cached::new(
+            super::MetadataServer::<_, String>::new(mock.clone()).host("127.0.0.1:8080"),
+        );
+        // a failure that is not transient is not cached
+        for _ in 0..2 {
+            mock.expect(Expectation::new(Method::GET, uri).status(StatusCode::FORBIDDEN));
+            provider.token(&[]).await.unwrap_err();
+        }
+        mock.assert_done();
+
+        assert_eq!(
+            provider.metrics(),
+            Metrics {
+                hits: 0,
+                coalesced: 0,
+                refreshes: 2,
+                failures: 2,
+                negative_hits: 0,
+                abandoned: 0,
+            },
+        );
+    }
+}
@@ -0,0 +1,70 @@
+// serves the XML and JSON object APIs over HTTP from a directory, see google_cloud_storage::fake
+//
+// usage: gcs-local [--addr ADDR] [--bucket NAME]... ROOT
+//
+// every subdirectory of ROOT is a bucket with its objects as flat files, see USAGE; clients use
+// path-style URLs such as http://127.0.0.1:4443/{bucket}/{object} and
+// http://127.0.0.1:4443/storage/v1/b/{bucket}/o
+//
+// requests are handled one at a time on a single thread: the fake writes every change to ROOT with
+// blocking file I/O while it holds its lock, so there is nothing to gain from more workers.
+
+use google_cloud_storage::fake::Service;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::net::TcpListener;
+
+const USAGE: &str = "usage: gcs-local [--addr ADDR] [--bucket NAME]... ROOT
+
+every subdirectory of ROOT is a bucket and every file directly in it is an object. the layout is
+flat: an object named a/b is stored as the file a%2Fb, and subdirectories of a bucket are not
+served, since a and a/b can both be objects but not both be a file and a directory.";
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let mut addr = SocketAddr::from(([127, 0, 0, 1], 4443));
+    let mut bucket_names = Vec::new();
+    let mut root = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = args.next().ok_or(USAGE)?.parse()?,
+            "--bucket" => bucket_names.push(args.next().ok_or(USAGE)?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ if root.is_none() && !arg.starts_with('-') => root = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument: {arg}\n{USAGE}").into()),
+        }
+    }
+    let root = root.ok_or(USAGE)?;
+
+    std::fs::create_dir_all(&root)?;
+    let service = Service::open(&root)?;
+    for bucket_name in bucket_names {
+        service.create_bucket(bucket_name)?;
+    }
+
+    let listener = TcpListener::bind(addr).await?;
+    eprintln!(
+        "serving {} on http://{}",
+        root.display(),
+        listener.local_addr()?,
+    );
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let service = TowerToHyperService::new(service.clone());
+        tokio::spawn(async move {
+            if let Err(e) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                eprintln!("{e}");
+            }
+        });
+    }
+}
@@ -0,0 +1,135 @@
+// https://cloud.google.com/storage/docs/json_api/v1/buckets#resource
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bucket {
+    #[serde(default)]
+    pub cors: Vec<Cors>,
+    pub encryption: Option<Encryption>,
+    pub id: String,
+    pub lifecycle: Option<Lifecycle>,
+    pub location: String,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub metageneration: u64,
+    pub name: String,
+    pub soft_delete_policy: Option<SoftDeletePolicy>,
+    pub storage_class: StorageClass,
+    pub versioning: Option<Versioning>,
+    pub website: Option<Website>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StorageClass {
+    Standard,
+    Nearline,
+    Coldline,
+    Archive,
+    MultiRegional,
+    Regional,
+    DurableReducedAvailability,
+}
+
+// https://cloud.google.com/storage/docs/encryption/using-customer-managed-keys#add-default-key
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Encryption {
+    // projects/{project}/locations/{location}/keyRings/{ring}/cryptoKeys/{key}
+    pub default_kms_key_name: Option<String>,
+}
+
+// https://cloud.google.com/storage/docs/lifecycle
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Lifecycle {
+    #[serde(default)]
+    pub rule: Vec<Rule>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Rule {
+    pub action: Action,
+    pub condition: Condition,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum Action {
+    Delete,
+    SetStorageClass { storage_class: StorageClass },
+    AbortIncompleteMultipartUpload,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<u32>,
+    // YYYY-MM-DD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<String>,
+    // YYYY-MM-DD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_time_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_since_custom_time: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_since_noncurrent_time: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_live: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matches_prefix: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matches_storage_class: Vec<StorageClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matches_suffix: Vec<String>,
+    // YYYY-MM-DD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noncurrent_time_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_newer_versions: Option<u32>,
+}
+
+// https://cloud.google.com/storage/docs/cross-origin
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cors {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_seconds: Option<u32>,
+    #[serde_as(as = "Vec<serde_with::DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub method: Vec<http::Method>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub origin: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_header: Vec<String>,
+}
+
+// https://cloud.google.com/storage/docs/object-versioning
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Versioning {
+    pub enabled: bool,
+}
+
+// https://cloud.google.com/storage/docs/soft-delete
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftDeletePolicy {
+    #[serde(default, skip_serializing, with = "time::serde::rfc3339::option")]
+    pub effective_time: Option<time::OffsetDateTime>,
+    // 0 disables soft delete
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub retention_duration_seconds: u64,
+}
+
+// https://cloud.google.com/storage/docs/static-website
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Website {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub main_page_suffix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_found_page: Option<String>,
+}
@@ -0,0 +1,677 @@
+// records request/response pairs to a file while talking to a real service and replays them later
+// without a network
+//
+// credentials are scrubbed before anything is written: authorization and encryption key headers,
+// secret fields of JSON bodies (HMAC key secrets and access tokens) and the signature and
+// credential of signed URLs are replaced with REDACTED. values that change between runs are
+// replaced with placeholders: UUIDs (as used for generated object names) become
+// 00000000-0000-0000-0000-{index}, and fixed values such as the bucket name are registered with
+// Layer::replace. replaying applies the same replacements to the outgoing requests, so tests that
+// generate fresh object names still match the recorded requests.
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use http::header::{HeaderName, AUTHORIZATION, CONTENT_LENGTH, PROXY_AUTHORIZATION};
+use http::{HeaderMap, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+const SCRUBBED: [HeaderName; 4] = [
+    AUTHORIZATION,
+    PROXY_AUTHORIZATION,
+    HeaderName::from_static("x-goog-copy-source-encryption-key"),
+    HeaderName::from_static("x-goog-encryption-key"),
+];
+
+// fields of JSON bodies, at any depth
+const SCRUBBED_FIELDS: [&str; 3] = ["accessToken", "access_token", "secret"];
+
+// query parameters of signed URLs, matched case-insensitively wherever they appear
+const SCRUBBED_PARAMETERS: [&str; 2] = ["x-goog-credential=", "x-goog-signature="];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<S> {
+    #[error(transparent)]
+    Body(BoxError),
+    #[error(transparent)]
+    Io(io::Error),
+    #[error("request does not match the cassette: {0}")]
+    Mismatch(String),
+    #[error(transparent)]
+    Service(S),
+}
+
+#[derive(Clone)]
+pub struct Service<S> {
+    inner: Option<S>,
+    state: Arc<Mutex<State>>,
+}
+
+impl<S> Service<S> {
+    // serves the interactions recorded at path in order, without an inner service
+    pub fn replay<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let interactions = serde_json::from_slice(&fs::read(path)?).map_err(io::Error::other)?;
+        Ok(Self {
+            inner: None,
+            state: Arc::new(Mutex::new(State {
+                interactions,
+                mode: Mode::Replay(0),
+                names: Names::default(),
+            })),
+        })
+    }
+}
+
+impl<S, B, R> tower::Service<Request<B>> for Service<S>
+where
+    S: Clone + tower::Service<Request<Full<Bytes>>, Response = Response<R>> + Send + 'static,
+    S::Error: Send,
+    S::Future: Send,
+    B: http_body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    R: http_body::Body + Send,
+    R::Data: Send,
+    R::Error: Into<BoxError>,
+{
+    type Response = Response<Full<Bytes>>;
+    type Error = Error<S::Error>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match &mut self.inner {
+            Some(inner) => inner.poll_ready(cx).map_err(Error::Service),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let inner = self.inner.clone();
+        let inner = mem::replace(&mut self.inner, inner);
+        let state = self.state.clone();
+        async move {
+            let (parts, body) = request.into_parts();
+            let body = body
+                .collect()
+                .await
+                .map_err(|e| Error::Body(e.into()))?
+                .to_bytes();
+            let recorded = state.lock().unwrap().names.request(&parts, &body);
+            let Some(mut inner) = inner else {
+                return state
+                    .lock()
+                    .unwrap()
+                    .replay(&recorded)
+                    .map_err(Error::Mismatch);
+            };
+
+            let response = inner
+                .call(Request::from_parts(parts, Full::new(body)))
+                .await
+                .map_err(Error::Service)?;
+            let (parts, body) = response.into_parts();
+            let body = body
+                .collect()
+                .await
+                .map_err(|e| Error::Body(e.into()))?
+                .to_bytes();
+            state
+                .lock()
+                .unwrap()
+                .record(recorded, &parts, &body)
+                .map_err(Error::Io)?;
+            Ok(Response::from_parts(parts, Full::new(body)))
+        }
+        .boxed()
+    }
+}
+
+#[derive(Clone)]
+pub struct Layer {
+    state: Arc<Mutex<State>>,
+}
+
+impl<S> tower::Layer<S> for Layer {
+    type Service = Service<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Service {
+            inner: Some(inner),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Layer {
+    // forwards requests to the inner service and (over)writes the cassette at path after every
+    // response
+    pub fn record<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                interactions: Vec::new(),
+                mode: Mode::Record(path.into()),
+                names: Names::default(),
+            })),
+        }
+    }
+
+    pub fn replace<A, P>(self, actual: A, placeholder: P) -> Self
+    where
+        A: Into<String>,
+        P: Into<String>,
+    {
+        self.state
+            .lock()
+            .unwrap()
+            .names
+            .replacements
+            .push((actual.into(), placeholder.into()));
+        self
+    }
+}
+
+struct State {
+    interactions: Vec<Interaction>,
+    mode: Mode,
+    names: Names,
+}
+
+enum Mode {
+    Record(PathBuf),
+    Replay(usize),
+}
+
+impl State {
+    fn record(
+        &mut self,
+        request: RecordedRequest,
+        parts: &http::response::Parts,
+        body: &Bytes,
+    ) -> io::Result<()> {
+        let Mode::Record(path) = &self.mode else {
+            unreachable!()
+        };
+        let mut response = RecordedResponse {
+            status: parts.status.as_u16(),
+            headers: self.names.headers(&parts.headers),
+            body: self.names.data(body),
+        };
+        if response.body.len() != body.len() {
+            set_content_length(&mut response.headers, response.body.len());
+        }
+        self.interactions.push(Interaction { request, response });
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(
+            path,
+            serde_json::to_vec_pretty(&self.interactions).map_err(io::Error::other)?,
+        )
+    }
+
+    fn replay(&mut self, request: &RecordedRequest) -> Result<Response<Full<Bytes>>, String> {
+        let Mode::Replay(position) = &mut self.mode else {
+            unreachable!()
+        };
+        let Some(Interaction {
+            request: expected,
+            response,
+        }) = self.interactions.get(*position)
+        else {
+            return Err(format!(
+                "unexpected request #{position}: {} {}",
+                request.method, request.uri,
+            ));
+        };
+        if (&expected.method, &expected.uri) != (&request.method, &request.uri) {
+            return Err(format!(
+                "request #{position}: expected {} {}, got {} {}",
+                expected.method, expected.uri, request.method, request.uri,
+            ));
+        }
+        if expected.body != request.body {
+            return Err(format!(
+                "request #{position} ({} {}): expected body {:?}, got {:?}",
+                request.method, request.uri, expected.body, request.body,
+            ));
+        }
+        *position += 1;
+
+        let body = match &response.body {
+            Data::Text(value) => Bytes::from(self.names.restore(value)),
+            Data::Base64(value) => Bytes::copy_from_slice(value),
+        };
+        let mut headers = response
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), self.names.restore(value)))
+            .collect::<Vec<_>>();
+        if response.body.len() != body.len() {
+            set_content_length(&mut headers, body.len());
+        }
+
+        let mut builder = Response::builder()
+            .status(StatusCode::from_u16(response.status).map_err(|e| e.to_string())?);
+        for (name, value) in headers {
+            builder = builder.header(name, value);
+        }
+        builder.body(Full::new(body)).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordedRequest {
+    body: Data,
+    headers: Vec<(String, String)>,
+    method: String,
+    uri: String,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordedResponse {
+    body: Data,
+    headers: Vec<(String, String)>,
+    status: u16,
+}
+
+#[serde_with::serde_as]
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Data {
+    Base64(#[serde_as(as = "serde_with::base64::Base64")] Vec<u8>),
+    Text(String),
+}
+
+impl Data {
+    fn len(&self) -> usize {
+        match self {
+            Self::Base64(value) => value.len(),
+            Self::Text(value) => value.len(),
+        }
+    }
+}
+
+fn set_content_length(headers: &mut [(String, String)], len: usize) {
+    for (name, value) in headers {
+        if name.eq_ignore_ascii_case(CONTENT_LENGTH.as_str()) {
+            *value = len.to_string();
+        }
+    }
+}
+
+#[derive(Default)]
+struct Names {
+    replacements: Vec<(String, String)>,
+    uuids: Vec<String>,
+}
+
+impl Names {
+    fn request(&mut self, parts: &http::request::Parts, body: &Bytes) -> RecordedRequest {
+        RecordedRequest {
+            body: self.data(body),
+            headers: self.headers(&parts.headers),
+            method: parts.method.to_string(),
+            uri: self.normalize(&parts.uri.to_string()),
+        }
+    }
+
+    fn headers(&mut self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if SCRUBBED.contains(name) {
+                    "REDACTED".to_owned()
+                } else {
+                    self.normalize(&String::from_utf8_lossy(value.as_bytes()))
+                };
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+
+    fn data(&mut self, body: &Bytes) -> Data {
+        match std::str::from_utf8(body) {
+            Ok(value) => match scrub_json(value) {
+                Some(value) => Data::Text(self.normalize(&value)),
+                None => Data::Text(self.normalize(value)),
+            },
+            Err(_) => Data::Base64(body.to_vec()),
+        }
+    }
+
+    // actual values to placeholders
+    fn normalize(&mut self, value: &str) -> String {
+        let value = self
+            .replacements
+            .iter()
+            .fold(scrub_parameters(value), |value, (actual, placeholder)| {
+                value.replace(actual, placeholder)
+            });
+        replace_uuids(&value, |hex| {
+            if placeholder_index(hex).is_some() {
+                return None;
+            }
+            let index = match self.uuids.iter().position(|uuid| uuid == hex) {
+                Some(index) => index,
+                None => {
+                    self.uuids.push(hex.to_owned());
+                    self.uuids.len() - 1
+                }
+            };
+            Some(format!("{:032x}", index + 1))
+        })
+    }
+
+    // placeholders to actual values. placeholders that were first seen in a response (e.g. IDs
+    // generated by the server) are kept as is, since requests send them back unchanged.
+    fn restore(&self, value: &str) -> String {
+        let value = replace_uuids(value, |hex| {
+            self.uuids
+                .get(placeholder_index(hex)?.checked_sub(1)?)
+                .cloned()
+        });
+        self.replacements
+            .iter()
+            .fold(value, |value, (actual, placeholder)| {
+                value.replace(placeholder, actual)
+            })
+    }
+}
+
+// the body with its secret fields redacted, or None if it is not JSON or has none, so that other
+// bodies are recorded byte for byte
+fn scrub_json(value: &str) -> Option<String> {
+    fn scrub(value: &mut serde_json::Value) -> bool {
+        match value {
+            serde_json::Value::Array(values) => values
+                .iter_mut()
+                .fold(false, |found, value| scrub(value) | found),
+            serde_json::Value::Object(map) => map.iter_mut().fold(false, |found, (key, value)| {
+                if SCRUBBED_FIELDS.contains(&key.as_str()) {
+                    *value = serde_json::Value::from("REDACTED");
+                    true
+                } else {
+                    scrub(value) | found
+                }
+            }),
+            _ => false,
+        }
+    }
+
+    let mut json = serde_json::from_str(value).ok()?;
+    scrub(&mut json).then(|| json.to_string())
+}
+
+fn scrub_parameters(value: &str) -> String {
+    // lowercasing ASCII keeps the byte offsets of value
+    let lowercase = value.to_ascii_lowercase();
+    let mut output = String::with_capacity(value.len());
+    let mut start = 0;
+    while let Some(i) = SCRUBBED_PARAMETERS
+        .iter()
+        .filter_map(|parameter| {
+            let i = lowercase[start..].find(parameter)?;
+            Some(start + i + parameter.len())
+        })
+        .min()
+    {
+        output.push_str(&value[start..i]);
+        output.push_str("REDACTED");
+        start = value[i..]
+            .find(|c: char| matches!(c, '&' | '#' | '"' | '\'' | '<' | '>') || c.is_whitespace())
+            .map_or(value.len(), |end| i + end);
+    }
+    output.push_str(&value[start..]);
+    output
+}
+
+fn placeholder_index(hex: &str) -> Option<usize> {
+    if hex.starts_with("00000000000000000000") {
+        usize::from_str_radix(hex, 16).ok()
+    } else {
+        None
+    }
+}
+
+// calls f with the 32 hex digits of every UUID in value. hyphens may be percent-encoded.
+fn replace_uuids<F>(value: &str, mut f: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    const GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+
+    let bytes = value.as_bytes();
+    let mut output = String::with_capacity(value.len());
+    let (mut start, mut i) = (0, 0);
+    'outer: while i < bytes.len() {
+        if i > 0 && bytes[i - 1].is_ascii_alphanumeric() {
+            i += 1;
+            continue;
+        }
+        let mut hex = String::with_capacity(32);
+        let mut separators = Vec::with_capacity(4);
+        let mut j = i;
+        for (k, len) in GROUPS.into_iter().enumerate() {
+            if k > 0 {
+                let separator = if bytes[j..].starts_with(b"-") {
+                    1
+                } else if bytes[j..].len() >= 3 && bytes[j..j + 3].eq_ignore_ascii_case(b"%2D") {
+                    3
+                } else {
+                    i += 1;
+                    continue 'outer;
+                };
+                separators.push(&value[j..j + separator]);
+                j += separator;
+            }
+            match bytes.get(j..j + len) {
+                Some(group) if group.iter().all(u8::is_ascii_hexdigit) => {
+                    hex.push_str(&value[j..j + len]);
+                    j += len;
+                }
+                _ => {
+                    i += 1;
+                    continue 'outer;
+                }
+            }
+        }
+        if bytes.get(j).is_some_and(u8::is_ascii_alphanumeric) {
+            i += 1;
+            continue;
+        }
+
+        output.push_str(&value[start..i]);
+        match f(&hex) {
+            Some(hex) => {
+                let mut offset = 0;
+                for (k, len) in GROUPS.into_iter().enumerate() {
+                    if k > 0 {
+                        output.push_str(separators[k - 1]);
+                    }
+                    output.push_str(&hex[offset..offset + len]);
+                    offset += len;
+                }
+            }
+            None => output.push_str(&value[i..j]),
+        }
+        (start, i) = (j, j);
+    }
+    output.push_str(&value[start..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api;
+    use bytes::Bytes;
+    use http_body_util::{BodyExt, Full};
+    use tower::{Layer, ServiceExt};
+
+    fn body(data: &'static [u8]) -> Full<Bytes> {
+        Full::from(Bytes::from_static(data))
+    }
+
+    async fn put_and_get<S>(service: S, bucket_name: &str, object_name: &str) -> Bytes
+    where
+        S: Clone
+            + tower::Service<
+                http::Request<Full<Bytes>>,
+                Response = http::Response<Full<Bytes>>,
+                Error = super::Error<std::convert::Infallible>,
+            >,
+    {
+        api::xml::put_object::builder(bucket_name, object_name, body(b"hello"))
+            .send(service.clone())
+            .await
+            .unwrap();
+        let response = api::xml::get_object::builder(bucket_name, object_name)
+            .send::<_, Full<Bytes>, _>(service)
+            .await
+            .unwrap();
+        response.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let path = std::env::temp_dir()
+            .join(uuid::Uuid::new_v4().to_string())
+            .join("cassette.json");
+
+        {
+            let fake = crate::fake::Service::new();
+            fake.create_bucket("actual-bucket").unwrap();
+            let service = super::Layer::record(&path)
+                .replace("actual-bucket", "bucket")
+                .layer(fake.map_request(|mut request: http::Request<Full<Bytes>>| {
+                    request.headers_mut().insert(
+                        http::header::AUTHORIZATION,
+                        "Bearer secret".parse().unwrap(),
+                    );
+                    request
+                }));
+            let object_name = uuid::Uuid::new_v4().to_string();
+            let data = put_and_get(service, "actual-bucket", &object_name).await;
+            assert_eq!(data, b"hello".as_slice());
+
+            let cassette = std::fs::read_to_string(&path).unwrap();
+            assert!(!cassette.contains("actual-bucket"));
+            assert!(!cassette.contains(&object_name));
+            assert!(!cassette.contains("secret"));
+            assert!(cassette.contains(
+                "https://bucket.storage.googleapis.com/00000000%2D0000%2D0000%2D0000%2D000000000001"
+            ));
+        }
+        {
+            let service = super::Service::<crate::fake::Service>::replay(&path).unwrap();
+            let object_name = uuid::Uuid::new_v4().to_string();
+            let data = put_and_get(service.clone(), "bucket", &object_name).await;
+            assert_eq!(data, b"hello".as_slice());
+
+            let e = api::xml::head_object::builder("bucket", &object_name)
+                .send::<_, Full<Bytes>, _>(service)
+                .await
+                .unwrap_err();
+            assert!(matches!(e, api::Error::Service(super::Error::Mismatch(_))));
+        }
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_replace_uuids() {
+        let mut names = super::Names::default();
+        let a = "0b6f4ac4-1a2b-4c3d-8e9f-0123456789ab";
+        let b = "9c0d4e5f-6a7b-4c8d-9eaf-b0c1d2e3f4a5";
+        let value = format!("/b/{a}/{b}?prefix={}&x={a}", a.replace('-', "%2D"));
+        let normalized = names.normalize(&value);
+        assert_eq!(
+            normalized,
+            "/b/00000000-0000-0000-0000-000000000001/00000000-0000-0000-0000-000000000002\
+             ?prefix=00000000%2D0000%2D0000%2D0000%2D000000000001\
+             &x=00000000-0000-0000-0000-000000000001",
+        );
+        assert_eq!(names.normalize(&normalized), normalized);
+        assert_eq!(names.restore(&normalized), value);
+        // not a UUID on its own
+        let value = format!("x{a}");
+        assert_eq!(names.normalize(&value), value);
+    }
+
+    #[test]
+    fn test_scrub() {
+        let mut names = super::Names::default();
+        let body = br#"{"metadata":{"accessId":"GOOG1"},"secret":"c2VjcmV0"}"#;
+        assert_eq!(
+            names.data(&bytes::Bytes::from_static(body)),
+            super::Data::Text(
+                r#"{"metadata":{"accessId":"GOOG1"},"secret":"REDACTED"}"#.to_owned()
+            ),
+        );
+        let body =
+            br#"{"items": [{"accessToken": "ya29.a", "expireTime": "2025-01-01T00:00:00Z"}]}"#;
+        assert_eq!(
+            names.data(&bytes::Bytes::from_static(body)),
+            super::Data::Text(
+                r#"{"items":[{"accessToken":"REDACTED","expireTime":"2025-01-01T00:00:00Z"}]}"#
+                    .to_owned()
+            ),
+        );
+        // other bodies are kept as they are
+        let body = br#"{"kind": "storage#object"}"#;
+        assert_eq!(
+            names.data(&bytes::Bytes::from_static(body)),
+            super::Data::Text(r#"{"kind": "storage#object"}"#.to_owned()),
+        );
+
+        let uri = "https://bucket.storage.googleapis.com/object\
+                   ?X-Goog-Algorithm=GOOG4-RSA-SHA256\
+                   &X-Goog-Credential=sa%40project.iam.gserviceaccount.com%2F20250101%2Fauto%2Fstorage%2Fgoog4_request\
+                   &X-Goog-Date=20250101T000000Z\
+                   &X-Goog-Expires=900\
+                   &X-Goog-SignedHeaders=host\
+                   &x-goog-signature=0123abcd";
+        let normalized = names.normalize(uri);
+        assert_eq!(
+            normalized,
+            "https://bucket.storage.googleapis.com/object\
+             ?X-Goog-Algorithm=GOOG4-RSA-SHA256\
+             &X-Goog-Credential=REDACTED\
+             &X-Goog-Date=20250101T000000Z\
+             &X-Goog-Expires=900\
+             &X-Goog-SignedHeaders=host\
+             &x-goog-signature=REDACTED",
+        );
+        assert_eq!(names.normalize(&normalized), normalized);
+        let body = br#"{"url":"https://example.com/o?X-Goog-Signature=ab12"}"#;
+        assert_eq!(
+            names.data(&bytes::Bytes::from_static(body)),
+            super::Data::Text(
+                r#"{"url":"https://example.com/o?X-Goog-Signature=REDACTED"}"#.to_owned()
+            ),
+        );
+    }
+}
@@ -0,0 +1,213 @@
+// https://cloud.google.com/eventarc/docs/cloudevents#cloud-storage
+// https://github.com/cloudevents/spec/blob/v1.0.2/cloudevents/bindings/http-protocol-binding.md
+
+use crate::object::Object;
+use http::HeaderMap;
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct CloudEvent {
+    pub id: String,
+    // //storage.googleapis.com/projects/_/buckets/{bucket}
+    pub source: String,
+    #[serde(rename = "specversion")]
+    pub spec_version: String,
+    // objects/{object}
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub time: OffsetDateTime,
+    pub r#type: EventType,
+    pub data: Object,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum EventType {
+    #[serde(rename = "google.cloud.storage.object.v1.archived")]
+    Archived,
+    #[serde(rename = "google.cloud.storage.object.v1.deleted")]
+    Deleted,
+    #[serde(rename = "google.cloud.storage.object.v1.finalized")]
+    Finalized,
+    #[serde(rename = "google.cloud.storage.object.v1.metadataUpdated")]
+    MetadataUpdated,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid header: {0}")]
+    InvalidHeader(&'static str),
+    #[error(transparent)]
+    Json(serde_json::Error),
+    #[error("missing header: {0}")]
+    MissingHeader(&'static str),
+}
+
+impl CloudEvent {
+    // detects the content mode by the content-type header
+    pub fn from_parts(headers: &HeaderMap, body: &[u8]) -> Result<Self, Error> {
+        let structured = headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<mime::Mime>().ok())
+            .is_some_and(|content_type| {
+                content_type.type_() == mime::APPLICATION
+                    && content_type.subtype() == "cloudevents"
+                    && content_type.suffix() == Some(mime::JSON)
+            });
+        if structured {
+            Self::from_structured(body)
+        } else {
+            Self::from_binary(headers, body)
+        }
+    }
+
+    pub fn from_structured(body: &[u8]) -> Result<Self, Error> {
+        serde_json::from_slice(body).map_err(Error::Json)
+    }
+
+    pub fn from_binary(headers: &HeaderMap, body: &[u8]) -> Result<Self, Error> {
+        fn get<'a>(headers: &'a HeaderMap, key: &'static str) -> Result<&'a str, Error> {
+            headers
+                .get(key)
+                .ok_or(Error::MissingHeader(key))?
+                .to_str()
+                .map_err(|_| Error::InvalidHeader(key))
+        }
+
+        Ok(Self {
+            id: get(headers, "ce-id")?.to_owned(),
+            source: get(headers, "ce-source")?.to_owned(),
+            spec_version: get(headers, "ce-specversion")?.to_owned(),
+            subject: headers
+                .contains_key("ce-subject")
+                .then(|| get(headers, "ce-subject").map(str::to_owned))
+                .transpose()?,
+            time: OffsetDateTime::parse(
+                get(headers, "ce-time")?,
+                &time::format_description::well_known::Rfc3339,
+            )
+            .map_err(|_| Error::InvalidHeader("ce-time"))?,
+            r#type: EventType::deserialize(serde::de::value::StrDeserializer::<
+                serde::de::value::Error,
+            >::new(get(headers, "ce-type")?))
+            .map_err(|_| Error::InvalidHeader("ce-type"))?,
+            data: serde_json::from_slice(body).map_err(Error::Json)?,
+        })
+    }
+
+    pub fn bucket(&self) -> Option<&str> {
+        self.source
+            .strip_prefix("//storage.googleapis.com/projects/_/buckets/")
+    }
+}
+
+impl<B> TryFrom<&http::Request<B>> for CloudEvent
+where
+    B: AsRef<[u8]>,
+{
+    type Error = Error;
+
+    fn try_from(value: &http::Request<B>) -> Result<Self, Self::Error> {
+        Self::from_parts(value.headers(), value.body().as_ref())
+    }
+}
+
+impl<B> TryFrom<http::Request<B>> for CloudEvent
+where
+    B: AsRef<[u8]>,
+{
+    type Error = Error;
+
+    fn try_from(value: http::Request<B>) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventType;
+    use time::macros::datetime;
+
+    fn data() -> serde_json::Value {
+        serde_json::json!({
+            "kind": "storage#object",
+            "id": "my-bucket/logs/app.log/1700000000000000",
+            "name": "logs/app.log",
+            "bucket": "my-bucket",
+            "generation": "1700000000000000",
+            "metageneration": "1",
+            "contentType": "text/plain",
+            "timeCreated": "2023-11-14T22:13:20.000Z",
+            "updated": "2023-11-14T22:13:20.000Z",
+            "storageClass": "STANDARD",
+            "size": "11",
+            "md5Hash": "XrY7u+Ae7tCTyyK7j1rNww==",
+            "crc32c": "yZRlqg==",
+        })
+    }
+
+    fn check(event: super::CloudEvent) {
+        assert_eq!(event.id, "1234567890");
+        assert_eq!(event.bucket(), Some("my-bucket"));
+        assert_eq!(event.spec_version, "1.0");
+        assert_eq!(event.subject.as_deref(), Some("objects/logs/app.log"));
+        assert_eq!(event.time, datetime!(2023-11-14 22:13:20 UTC));
+        assert_eq!(event.r#type, EventType::Finalized);
+        assert_eq!(event.data.name, "logs/app.log");
+        assert_eq!(event.data.generation, 1700000000000000);
+    }
+
+    #[test]
+    fn test_structured() {
+        let body = serde_json::json!({
+            "specversion": "1.0",
+            "id": "1234567890",
+            "source": "//storage.googleapis.com/projects/_/buckets/my-bucket",
+            "type": "google.cloud.storage.object.v1.finalized",
+            "subject": "objects/logs/app.log",
+            "time": "2023-11-14T22:13:20.000000Z",
+            "datacontenttype": "application/json",
+            "data": data(),
+        });
+        let request = http::Request::post("/")
+            .header(
+                http::header::CONTENT_TYPE,
+                "application/cloudevents+json; charset=utf-8",
+            )
+            .body(body.to_string())
+            .unwrap();
+        check(super::CloudEvent::try_from(request).unwrap());
+    }
+
+    #[test]
+    fn test_binary() {
+        let request = http::Request::post("/")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header("ce-specversion", "1.0")
+            .header("ce-id", "1234567890")
+            .header(
+                "ce-source",
+                "//storage.googleapis.com/projects/_/buckets/my-bucket",
+            )
+            .header("ce-type", "google.cloud.storage.object.v1.finalized")
+            .header("ce-subject", "objects/logs/app.log")
+            .header("ce-time", "2023-11-14T22:13:20.000000Z")
+            .body(data().to_string())
+            .unwrap();
+        check(super::CloudEvent::try_from(&request).unwrap());
+    }
+
+    #[test]
+    fn test_binary_missing_header() {
+        let request = http::Request::post("/")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(data().to_string())
+            .unwrap();
+        assert!(matches!(
+            super::CloudEvent::try_from(&request),
+            Err(super::Error::MissingHeader("ce-id")),
+        ));
+    }
+}
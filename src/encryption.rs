@@ -0,0 +1,145 @@
+// https://cloud.google.com/storage/docs/encryption/customer-supplied-keys
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use http::{HeaderName, HeaderValue};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+static X_GOOG_ENCRYPTION_ALGORITHM: HeaderName =
+    HeaderName::from_static("x-goog-encryption-algorithm");
+static X_GOOG_ENCRYPTION_KEY: HeaderName = HeaderName::from_static("x-goog-encryption-key");
+static X_GOOG_ENCRYPTION_KEY_SHA256: HeaderName =
+    HeaderName::from_static("x-goog-encryption-key-sha256");
+static X_GOOG_COPY_SOURCE_ENCRYPTION_ALGORITHM: HeaderName =
+    HeaderName::from_static("x-goog-copy-source-encryption-algorithm");
+static X_GOOG_COPY_SOURCE_ENCRYPTION_KEY: HeaderName =
+    HeaderName::from_static("x-goog-copy-source-encryption-key");
+static X_GOOG_COPY_SOURCE_ENCRYPTION_KEY_SHA256: HeaderName =
+    HeaderName::from_static("x-goog-copy-source-encryption-key-sha256");
+
+pub const ALGORITHM: &str = "AES256";
+
+// an AES-256 key; never sent anywhere but in the request headers
+#[derive(Clone, PartialEq, Eq)]
+pub struct CustomerEncryptionKey([u8; 32]);
+
+impl fmt::Debug for CustomerEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomerEncryptionKey")
+            .field("key_sha256", &BASE64_STANDARD.encode(self.key_sha256()))
+            .finish_non_exhaustive()
+    }
+}
+
+impl CustomerEncryptionKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    pub fn key_sha256(&self) -> [u8; 32] {
+        Sha256::digest(self.0).into()
+    }
+
+    // x-goog-encryption-*
+    pub fn headers(&self) -> [(HeaderName, HeaderValue); 3] {
+        self.headers_with([
+            &X_GOOG_ENCRYPTION_ALGORITHM,
+            &X_GOOG_ENCRYPTION_KEY,
+            &X_GOOG_ENCRYPTION_KEY_SHA256,
+        ])
+    }
+
+    // x-goog-copy-source-encryption-*, for the source object of copies and rewrites
+    pub fn copy_source_headers(&self) -> [(HeaderName, HeaderValue); 3] {
+        self.headers_with([
+            &X_GOOG_COPY_SOURCE_ENCRYPTION_ALGORITHM,
+            &X_GOOG_COPY_SOURCE_ENCRYPTION_KEY,
+            &X_GOOG_COPY_SOURCE_ENCRYPTION_KEY_SHA256,
+        ])
+    }
+
+    fn headers_with(
+        &self,
+        [algorithm, key, key_sha256]: [&HeaderName; 3],
+    ) -> [(HeaderName, HeaderValue); 3] {
+        let mut key_value = HeaderValue::from_str(&BASE64_STANDARD.encode(self.0)).unwrap();
+        key_value.set_sensitive(true);
+        [
+            (algorithm.clone(), HeaderValue::from_static(ALGORITHM)),
+            (key.clone(), key_value),
+            (
+                key_sha256.clone(),
+                HeaderValue::from_str(&BASE64_STANDARD.encode(self.key_sha256())).unwrap(),
+            ),
+        ]
+    }
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects#customerEncryption
+#[serde_with::serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerEncryption {
+    pub encryption_algorithm: String,
+    #[serde_as(as = "serde_with::base64::Base64")]
+    pub key_sha256: [u8; 32],
+}
+
+// https://cloud.google.com/storage/docs/encryption/customer-managed-keys
+// splits projects/{project}/locations/{location}/keyRings/{ring}/cryptoKeys/{key}/cryptoKeyVersions/{version}
+// into the key name and the version
+pub fn split_kms_key_version(kms_key_name: &str) -> (&str, Option<&str>) {
+    match kms_key_name.rsplit_once("/cryptoKeyVersions/") {
+        Some((key, version)) => (key, Some(version)),
+        None => (kms_key_name, None),
+    }
+}
+
+pub(crate) fn with_headers<I>(
+    mut builder: http::request::Builder,
+    headers: I,
+) -> http::request::Builder
+where
+    I: IntoIterator<Item = (HeaderName, HeaderValue)>,
+{
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    #[test]
+    fn test_headers() {
+        let key = super::CustomerEncryptionKey::new([0; 32]);
+        let headers = key.headers();
+        assert_eq!(headers[0].0, "x-goog-encryption-algorithm");
+        assert_eq!(headers[0].1, "AES256");
+        assert_eq!(headers[1].0, "x-goog-encryption-key");
+        assert_eq!(headers[1].1, "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        assert!(headers[1].1.is_sensitive());
+        assert_eq!(headers[2].0, "x-goog-encryption-key-sha256");
+        assert_eq!(headers[2].1, "Zmh6rfhivXdsj8GLjp+OIAiXFIVu4jOzkCpZHQ1fKSU=");
+        assert_eq!(
+            key.key_sha256(),
+            hex!("66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"),
+        );
+        let headers = key.copy_source_headers();
+        assert_eq!(headers[0].0, "x-goog-copy-source-encryption-algorithm");
+        assert_eq!(headers[1].0, "x-goog-copy-source-encryption-key");
+        assert_eq!(headers[2].0, "x-goog-copy-source-encryption-key-sha256");
+    }
+
+    #[test]
+    fn test_split_kms_key_version() {
+        let key = "projects/my-project/locations/us/keyRings/my-ring/cryptoKeys/my-key";
+        assert_eq!(
+            super::split_kms_key_version(&format!("{key}/cryptoKeyVersions/3")),
+            (key, Some("3")),
+        );
+        assert_eq!(super::split_kms_key_version(key), (key, None));
+    }
+}
@@ -0,0 +1,885 @@
+// an in-memory stand-in for Cloud Storage that speaks the XML and JSON APIs emitted by this crate
+
+mod disk;
+mod json;
+mod xml;
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use http_body_util::{BodyExt, Full};
+use md5::{Digest, Md5};
+use sha2::Sha256;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use time::OffsetDateTime;
+
+#[derive(Clone, Default)]
+pub struct Service {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    buckets: BTreeMap<String, Bucket>,
+    // the buckets before the request being handled, restored if its change cannot be persisted
+    backup: Option<BTreeMap<String, Bucket>>,
+    // buckets and objects changed since the last flush, with an empty object name for a bucket
+    dirty: BTreeSet<(String, String)>,
+    generation: u64,
+    // set when the state is persisted to a directory
+    root: Option<PathBuf>,
+    sessions: HashMap<String, Session>,
+    upload_id: u64,
+}
+
+#[derive(Clone, Default)]
+struct Bucket {
+    // generations in ascending order; the last one is live unless it has time_deleted
+    objects: BTreeMap<String, Vec<Object>>,
+    versioning: bool,
+}
+
+#[derive(Clone)]
+struct Object {
+    name: String,
+    attributes: Attributes,
+    component_count: Option<u32>,
+    data: Bytes,
+    generation: u64,
+    metageneration: u64,
+    time_created: OffsetDateTime,
+    time_deleted: Option<OffsetDateTime>,
+    updated: OffsetDateTime,
+}
+
+#[derive(Clone, Default)]
+struct Attributes {
+    content_type: Option<String>,
+    customer_key_sha256: Option<[u8; 32]>,
+    kms_key_name: Option<String>,
+    metadata: BTreeMap<String, String>,
+}
+
+struct Session {
+    bucket_name: String,
+    object_name: String,
+    attributes: Attributes,
+    data: Vec<u8>,
+    preconditions: Preconditions,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Preconditions {
+    if_generation_match: Option<u64>,
+    if_generation_not_match: Option<u64>,
+    if_metageneration_match: Option<u64>,
+    if_metageneration_not_match: Option<u64>,
+}
+
+struct ListOptions {
+    delimiter: Option<String>,
+    max_results: usize,
+    prefix: String,
+    start_after: Option<String>,
+    versions: bool,
+}
+
+struct List {
+    objects: Vec<Object>,
+    prefixes: Vec<String>,
+    // the last object name or prefix returned, set when the listing is truncated
+    next: Option<String>,
+}
+
+enum Upload {
+    Cancelled,
+    Complete(Box<Object>),
+    Incomplete(usize),
+}
+
+#[derive(Debug)]
+enum Error {
+    BadDigest,
+    CustomerEncryptionKeyMismatch,
+    Internal(String),
+    InvalidArgument(String),
+    InvalidRange,
+    NoSuchBucket(String),
+    NoSuchKey(String, String),
+    NoSuchUpload,
+    NotImplemented,
+    NotModified,
+    PreconditionFailed,
+    ResourceIsEncryptedWithCustomerEncryptionKey,
+}
+
+struct Request {
+    // the scheme and authority the request was sent to, used to build session URIs
+    origin: String,
+    method: Method,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+type Response = http::Response<Full<Bytes>>;
+
+impl Service {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // loads buckets from the subdirectories of root and writes every change back to it
+    pub fn open<P>(root: P) -> io::Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let root = root.into();
+        let mut state = State {
+            buckets: disk::load(&root)?,
+            ..State::default()
+        };
+        state.generation = state
+            .buckets
+            .values()
+            .flat_map(|bucket| bucket.objects.values().flatten())
+            .map(|object| object.generation)
+            .max()
+            .unwrap_or_default();
+        state.root = Some(root);
+        Ok(Self {
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    pub fn create_bucket<B>(&self, bucket_name: B) -> io::Result<()>
+    where
+        B: Into<String>,
+    {
+        let bucket_name = bucket_name.into();
+        let mut state = self.state.lock().unwrap();
+        state.buckets.entry(bucket_name.clone()).or_default();
+        state.dirty.insert((bucket_name, String::new()));
+        state.flush()
+    }
+
+    fn handle(&self, request: Request, host: &str) -> Response {
+        let mut state = self.state.lock().unwrap();
+        state.backup = (state.root.is_some()
+            && request.method != Method::GET
+            && request.method != Method::HEAD)
+            .then(|| state.buckets.clone());
+        if let Some(bucket_name) = host.strip_suffix(".storage.googleapis.com") {
+            let object_name = decode(request.path.trim_start_matches('/'));
+            xml::handle(&mut state, request, bucket_name, &object_name)
+        } else if request.path.starts_with("/storage/v1/") || request.path.starts_with("/upload/") {
+            json::handle(&mut state, request)
+        } else {
+            let path = request.path.trim_start_matches('/');
+            let (bucket_name, object_name) = path.split_once('/').unwrap_or((path, ""));
+            let (bucket_name, object_name) = (decode(bucket_name), decode(object_name));
+            xml::handle(&mut state, request, &bucket_name, &object_name)
+        }
+    }
+}
+
+impl<B> tower::Service<http::Request<B>> for Service
+where
+    B: http_body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Send,
+{
+    type Response = Response;
+    type Error = B::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let this = self.clone();
+        async move {
+            let (parts, body) = request.into_parts();
+            let body = body.collect().await?.to_bytes();
+            let host = parts
+                .uri
+                .host()
+                .or_else(|| header(&parts.headers, http::header::HOST.as_str()))
+                .unwrap_or("storage.googleapis.com");
+            let origin = match (parts.uri.scheme_str(), parts.uri.authority()) {
+                (Some(scheme), Some(authority)) => format!("{scheme}://{authority}"),
+                _ => format!("http://{host}"),
+            };
+            let host = host.split(':').next().unwrap_or_default().to_owned();
+            let request = Request {
+                origin,
+                method: parts.method,
+                path: parts.uri.path().to_owned(),
+                query: parts
+                    .uri
+                    .query()
+                    .unwrap_or_default()
+                    .split('&')
+                    .filter(|pair| !pair.is_empty())
+                    .map(|pair| {
+                        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                        (decode(key), decode(value))
+                    })
+                    .collect(),
+                headers: parts.headers,
+                body,
+            };
+            Ok(this.handle(request, &host))
+        }
+        .boxed()
+    }
+}
+
+impl State {
+    fn bucket(&mut self, bucket_name: &str) -> Result<&mut Bucket, Error> {
+        self.buckets
+            .get_mut(bucket_name)
+            .ok_or_else(|| Error::NoSuchBucket(bucket_name.to_owned()))
+    }
+
+    // generations are microsecond timestamps, kept unique across the whole service
+    fn next_generation(&mut self) -> u64 {
+        let now = (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1000) as u64;
+        self.generation = now.max(self.generation + 1);
+        self.generation
+    }
+
+    fn get(
+        &mut self,
+        bucket_name: &str,
+        object_name: &str,
+        generation: Option<u64>,
+    ) -> Result<&mut Object, Error> {
+        let versions = self.bucket(bucket_name)?.objects.get_mut(object_name);
+        versions
+            .and_then(|versions| match generation {
+                Some(generation) => versions
+                    .iter_mut()
+                    .find(|object| object.generation == generation),
+                None => versions.last_mut().filter(|object| object.is_live()),
+            })
+            .ok_or_else(|| Error::NoSuchKey(bucket_name.to_owned(), object_name.to_owned()))
+    }
+
+    fn insert(
+        &mut self,
+        bucket_name: &str,
+        object_name: &str,
+        attributes: Attributes,
+        data: Bytes,
+        component_count: Option<u32>,
+        preconditions: Preconditions,
+    ) -> Result<Object, Error> {
+        attributes.check()?;
+        let generation = self.next_generation();
+        let bucket = self.bucket(bucket_name)?;
+        let versioning = bucket.versioning;
+        let live = bucket
+            .objects
+            .get(object_name)
+            .and_then(|versions| versions.last())
+            .filter(|object| object.is_live());
+        preconditions.check(live, false)?;
+        let versions = bucket.objects.entry(object_name.to_owned()).or_default();
+        let live = versions.last_mut().filter(|object| object.is_live());
+
+        let now = OffsetDateTime::now_utc();
+        if versioning {
+            if let Some(live) = live {
+                live.time_deleted = Some(now);
+            }
+        } else if live.is_some() {
+            versions.pop();
+        }
+        let object = Object {
+            name: object_name.to_owned(),
+            attributes: Attributes {
+                // the resource reports the key version used for the write
+                kms_key_name: attributes
+                    .kms_key_name
+                    .map(|kms_key_name| format!("{kms_key_name}/cryptoKeyVersions/1")),
+                ..attributes
+            },
+            component_count,
+            data,
+            generation,
+            metageneration: 1,
+            time_created: now,
+            time_deleted: None,
+            updated: now,
+        };
+        versions.push(object.clone());
+        self.touch(bucket_name, object_name);
+        Ok(object)
+    }
+
+    fn delete(
+        &mut self,
+        bucket_name: &str,
+        object_name: &str,
+        generation: Option<u64>,
+        preconditions: Preconditions,
+    ) -> Result<(), Error> {
+        let not_found = || Error::NoSuchKey(bucket_name.to_owned(), object_name.to_owned());
+        let bucket = self.bucket(bucket_name)?;
+        let versioning = bucket.versioning;
+        let versions = bucket.objects.get_mut(object_name).ok_or_else(not_found)?;
+        let index = match generation {
+            Some(generation) => versions
+                .iter()
+                .position(|object| object.generation == generation),
+            None => versions
+                .iter()
+                .rposition(Object::is_live)
+                .filter(|index| index + 1 == versions.len()),
+        }
+        .ok_or_else(not_found)?;
+        preconditions.check(Some(&versions[index]), false)?;
+        if versioning && generation.is_none() {
+            versions[index].time_deleted = Some(OffsetDateTime::now_utc());
+        } else {
+            versions.remove(index);
+        }
+        if versions.is_empty() {
+            bucket.objects.remove(object_name);
+        }
+        self.touch(bucket_name, object_name);
+        Ok(())
+    }
+
+    fn touch(&mut self, bucket_name: &str, object_name: &str) {
+        self.dirty
+            .insert((bucket_name.to_owned(), object_name.to_owned()));
+    }
+
+    // writes the changed buckets and objects to the root directory, if any. an entry stays dirty
+    // until it is written, so a failed flush is picked up again by the next one.
+    fn flush(&mut self) -> io::Result<()> {
+        let Some(root) = &self.root else {
+            self.dirty.clear();
+            return Ok(());
+        };
+        while let Some((bucket_name, object_name)) = self.dirty.first() {
+            if let Some(bucket) = self.buckets.get(bucket_name) {
+                if object_name.is_empty() {
+                    disk::store_bucket(root, bucket_name, bucket)?;
+                } else {
+                    let versions = bucket
+                        .objects
+                        .get(object_name)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+                    disk::store_object(root, bucket_name, object_name, versions)?;
+                }
+            }
+            self.dirty.pop_first();
+        }
+        Ok(())
+    }
+
+    // flushes after a request so that failures to persist are reported to the client. on failure
+    // the change is rolled back and everything it touched is rewritten by the next flush, which
+    // brings the directory back in line with the state.
+    fn commit(&mut self, response: Response) -> Result<Response, Error> {
+        let backup = self.backup.take();
+        let dirty = self.dirty.clone();
+        if let Err(e) = self.flush() {
+            if let Some(buckets) = backup {
+                self.buckets = buckets;
+            }
+            self.dirty.extend(dirty);
+            return Err(Error::Internal(e.to_string()));
+        }
+        Ok(response)
+    }
+
+    // object names and rolled-up prefixes in lexicographic order, each counted once
+    fn list(&mut self, bucket_name: &str, options: &ListOptions) -> Result<List, Error> {
+        let bucket = self.bucket(bucket_name)?;
+        let mut list = List {
+            objects: Vec::new(),
+            prefixes: Vec::new(),
+            next: None,
+        };
+        let mut last = None::<String>;
+        for (name, versions) in bucket.objects.range(options.prefix.clone()..) {
+            let Some(rest) = name.strip_prefix(&options.prefix) else {
+                break;
+            };
+            let versions = if options.versions {
+                versions.iter().rev().collect::<Vec<_>>()
+            } else {
+                versions
+                    .last()
+                    .filter(|object| object.is_live())
+                    .into_iter()
+                    .collect()
+            };
+            if versions.is_empty() {
+                continue;
+            }
+            let prefix = options.delimiter.as_deref().and_then(|delimiter| {
+                rest.find(delimiter)
+                    .map(|i| format!("{}{}", options.prefix, &rest[..i + delimiter.len()]))
+            });
+            let key = prefix.as_ref().unwrap_or(name);
+            if options
+                .start_after
+                .as_ref()
+                .is_some_and(|start_after| key <= start_after)
+                || last.as_ref() == Some(key)
+            {
+                continue;
+            }
+            if list.objects.len() + list.prefixes.len() >= options.max_results {
+                list.next = last;
+                break;
+            }
+            last = Some(key.clone());
+            match prefix {
+                Some(prefix) => list.prefixes.push(prefix),
+                None => list.objects.extend(versions.into_iter().cloned()),
+            }
+        }
+        Ok(list)
+    }
+
+    fn start_session(&mut self, session: Session) -> Result<String, Error> {
+        self.bucket(&session.bucket_name)?;
+        session.attributes.check()?;
+        self.upload_id += 1;
+        let upload_id = format!("fake-upload-{}", self.upload_id);
+        self.sessions.insert(upload_id.clone(), session);
+        Ok(upload_id)
+    }
+
+    // https://cloud.google.com/storage/docs/performing-resumable-uploads
+    fn upload(&mut self, request: &Request, upload_id: &str) -> Result<Upload, Error> {
+        if request.method == Method::DELETE {
+            return match self.sessions.remove(upload_id) {
+                Some(_) => Ok(Upload::Cancelled),
+                None => Err(Error::NoSuchUpload),
+            };
+        }
+        let session = self
+            .sessions
+            .get_mut(upload_id)
+            .ok_or(Error::NoSuchUpload)?;
+
+        // `bytes {first}-{last}/{total}`, `bytes {first}-{last}/*` or `bytes */{total}`
+        let invalid =
+            |value: &str| Error::InvalidArgument(format!("Invalid Content-Range: {value}"));
+        let total = match header(&request.headers, "content-range") {
+            Some(value) => {
+                let (range, total) = value
+                    .strip_prefix("bytes ")
+                    .and_then(|value| value.split_once('/'))
+                    .ok_or_else(|| invalid(value))?;
+                if range != "*" {
+                    let first = range
+                        .split_once('-')
+                        .and_then(|(first, _)| first.parse::<usize>().ok())
+                        .ok_or_else(|| invalid(value))?;
+                    if first > session.data.len() {
+                        return Err(invalid(value));
+                    }
+                    // bytes that were already persisted are ignored
+                    let skip = session.data.len() - first;
+                    session
+                        .data
+                        .extend_from_slice(request.body.get(skip..).unwrap_or_default());
+                }
+                match total {
+                    "*" => None,
+                    total => Some(total.parse::<usize>().map_err(|_| invalid(value))?),
+                }
+            }
+            None => {
+                session.data.extend_from_slice(&request.body);
+                Some(session.data.len())
+            }
+        };
+        if total != Some(session.data.len()) {
+            return Ok(Upload::Incomplete(session.data.len()));
+        }
+
+        let session = self.sessions.remove(upload_id).unwrap();
+        check_hashes(&request.headers, &session.data)?;
+        self.insert(
+            &session.bucket_name,
+            &session.object_name,
+            session.attributes,
+            Bytes::from(session.data),
+            None,
+            session.preconditions,
+        )
+        .map(|object| Upload::Complete(Box::new(object)))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn copy(
+        &mut self,
+        source_bucket_name: &str,
+        source_object_name: &str,
+        source_generation: Option<u64>,
+        source_customer_key_sha256: Option<[u8; 32]>,
+        bucket_name: &str,
+        object_name: &str,
+        attributes: Option<Attributes>,
+        encryption: Attributes,
+        preconditions: Preconditions,
+    ) -> Result<Object, Error> {
+        let source = self.get(source_bucket_name, source_object_name, source_generation)?;
+        source.check_customer_key(source_customer_key_sha256)?;
+        let source = source.clone();
+        let attributes = attributes.unwrap_or(source.attributes);
+        self.insert(
+            bucket_name,
+            object_name,
+            Attributes {
+                content_type: attributes.content_type,
+                customer_key_sha256: encryption.customer_key_sha256,
+                kms_key_name: encryption.kms_key_name,
+                metadata: attributes.metadata,
+            },
+            source.data,
+            source.component_count,
+            preconditions,
+        )
+    }
+}
+
+impl Attributes {
+    // everything here is sent back in response headers, so reject what is not a valid header
+    fn check(&self) -> Result<(), Error> {
+        let invalid = |key: &str, value: &str| {
+            Error::InvalidArgument(format!("Invalid value for {key}: {value:?}"))
+        };
+        let values = [
+            ("contentType", &self.content_type),
+            ("kmsKeyName", &self.kms_key_name),
+        ];
+        for (key, value) in values {
+            if let Some(value) = value {
+                HeaderValue::from_str(value).map_err(|_| invalid(key, value))?;
+            }
+        }
+        for (key, value) in &self.metadata {
+            HeaderName::from_bytes(format!("x-goog-meta-{key}").as_bytes())
+                .map_err(|_| invalid("metadata key", key))?;
+            HeaderValue::from_str(value).map_err(|_| invalid(key, value))?;
+        }
+        Ok(())
+    }
+}
+
+impl Preconditions {
+    fn from_query(query: &HashMap<String, String>) -> Result<Self, Error> {
+        let parse = |key: &str| {
+            query
+                .get(key)
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| Error::InvalidArgument(format!("Invalid {key}: {value}")))
+                })
+                .transpose()
+        };
+        Ok(Self {
+            if_generation_match: parse("ifGenerationMatch")?,
+            if_generation_not_match: parse("ifGenerationNotMatch")?,
+            if_metageneration_match: parse("ifMetagenerationMatch")?,
+            if_metageneration_not_match: parse("ifMetagenerationNotMatch")?,
+        })
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Result<Self, Error> {
+        let parse = |name: &str| {
+            header(headers, name)
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| Error::InvalidArgument(format!("Invalid {name}: {value}")))
+                })
+                .transpose()
+        };
+        Ok(Self {
+            if_generation_match: parse("x-goog-if-generation-match")?,
+            if_generation_not_match: None,
+            if_metageneration_match: parse("x-goog-if-metageneration-match")?,
+            if_metageneration_not_match: None,
+        })
+    }
+
+    // a missing object has generation 0; unmet not-match conditions on reads are 304
+    fn check(&self, object: Option<&Object>, read: bool) -> Result<(), Error> {
+        let generation = object.map_or(0, |object| object.generation);
+        let metageneration = object.map(|object| object.metageneration);
+        if self
+            .if_generation_match
+            .is_some_and(|value| value != generation)
+            || self
+                .if_metageneration_match
+                .is_some_and(|value| Some(value) != metageneration)
+        {
+            Err(Error::PreconditionFailed)
+        } else if self
+            .if_generation_not_match
+            .is_some_and(|value| value == generation)
+            || self
+                .if_metageneration_not_match
+                .is_some_and(|value| Some(value) == metageneration)
+        {
+            Err(if read {
+                Error::NotModified
+            } else {
+                Error::PreconditionFailed
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Object {
+    fn is_live(&self) -> bool {
+        self.time_deleted.is_none()
+    }
+
+    // composite objects have no MD5 hash
+    fn md5(&self) -> Option<[u8; 16]> {
+        self.component_count
+            .is_none()
+            .then(|| Md5::digest(&self.data).into())
+    }
+
+    fn crc32c(&self) -> [u8; 4] {
+        crc32c::crc32c(&self.data).to_be_bytes()
+    }
+
+    fn etag(&self) -> String {
+        match self.md5() {
+            Some(md5) => hex::encode(md5),
+            None => format!("{}-{}", hex::encode(self.crc32c()), self.generation),
+        }
+    }
+
+    fn content_type(&self) -> &str {
+        self.attributes
+            .content_type
+            .as_deref()
+            .unwrap_or("application/octet-stream")
+    }
+
+    fn check_customer_key(&self, key_sha256: Option<[u8; 32]>) -> Result<(), Error> {
+        match (self.attributes.customer_key_sha256, key_sha256) {
+            (None, _) => Ok(()),
+            (Some(_), None) => Err(Error::ResourceIsEncryptedWithCustomerEncryptionKey),
+            (Some(expected), Some(actual)) if expected == actual => Ok(()),
+            (Some(_), Some(_)) => Err(Error::CustomerEncryptionKeyMismatch),
+        }
+    }
+
+    // the x-goog-hash values, also used by the XML API for uploads
+    fn hashes(&self) -> impl Iterator<Item = String> {
+        [
+            Some(format!("crc32c={}", BASE64_STANDARD.encode(self.crc32c()))),
+            self.md5()
+                .map(|md5| format!("md5={}", BASE64_STANDARD.encode(md5))),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl Error {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::BadDigest
+            | Self::CustomerEncryptionKeyMismatch
+            | Self::InvalidArgument(_)
+            | Self::ResourceIsEncryptedWithCustomerEncryptionKey => StatusCode::BAD_REQUEST,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InvalidRange => StatusCode::RANGE_NOT_SATISFIABLE,
+            Self::NoSuchBucket(_) | Self::NoSuchKey(..) | Self::NoSuchUpload => {
+                StatusCode::NOT_FOUND
+            }
+            Self::NotImplemented => StatusCode::NOT_IMPLEMENTED,
+            Self::NotModified => StatusCode::NOT_MODIFIED,
+            Self::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Self::BadDigest => {
+                "Provided MD5 hash or CRC32C checksum doesn't match calculated value.".to_owned()
+            }
+            Self::CustomerEncryptionKeyMismatch => {
+                "The provided encryption key is incorrect.".to_owned()
+            }
+            Self::Internal(message) | Self::InvalidArgument(message) => message.clone(),
+            Self::InvalidRange => "The requested range cannot be satisfied.".to_owned(),
+            Self::NoSuchBucket(_) => "The specified bucket does not exist.".to_owned(),
+            // the XML API says "The specified key does not exist." and moves this to Details
+            Self::NoSuchKey(bucket_name, object_name) => {
+                format!("No such object: {bucket_name}/{object_name}")
+            }
+            Self::NoSuchUpload => "No such upload session.".to_owned(),
+            Self::NotImplemented => "The fake does not implement this request.".to_owned(),
+            Self::NotModified => String::new(),
+            Self::PreconditionFailed => {
+                "At least one of the pre-conditions you specified did not hold.".to_owned()
+            }
+            Self::ResourceIsEncryptedWithCustomerEncryptionKey => {
+                "The target object is encrypted by a customer-supplied encryption key.".to_owned()
+            }
+        }
+    }
+}
+
+// the x-goog-encryption-* or x-goog-copy-source-encryption-* headers
+fn customer_key_sha256(headers: &HeaderMap, prefix: &str) -> Result<Option<[u8; 32]>, Error> {
+    let Some(key) = header(headers, &format!("{prefix}-key")) else {
+        return Ok(None);
+    };
+    let invalid =
+        || Error::InvalidArgument("Missing or invalid encryption key headers.".to_owned());
+    if header(headers, &format!("{prefix}-algorithm")) != Some(crate::encryption::ALGORITHM) {
+        return Err(invalid());
+    }
+    let key = BASE64_STANDARD.decode(key).map_err(|_| invalid())?;
+    let key_sha256 = header(headers, &format!("{prefix}-key-sha256"))
+        .and_then(|value| BASE64_STANDARD.decode(value).ok())
+        .ok_or_else(invalid)?;
+    if key.len() != 32 || Sha256::digest(&key).as_slice() != key_sha256 {
+        return Err(invalid());
+    }
+    Ok(Some(key_sha256.try_into().unwrap()))
+}
+
+// the x-goog-hash and Content-MD5 headers sent with an upload
+fn check_hashes(headers: &HeaderMap, data: &[u8]) -> Result<(), Error> {
+    let md5 = BASE64_STANDARD.encode(Md5::digest(data));
+    let crc32c = BASE64_STANDARD.encode(crc32c::crc32c(data).to_be_bytes());
+    let hashes = headers
+        .get_all("x-goog-hash")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|value| value.trim().split_once('='));
+    for (algorithm, value) in hashes {
+        match algorithm {
+            "md5" if value != md5 => return Err(Error::BadDigest),
+            "crc32c" if value != crc32c => return Err(Error::BadDigest),
+            _ => (),
+        }
+    }
+    if header(headers, "content-md5").is_some_and(|value| value != md5) {
+        return Err(Error::BadDigest);
+    }
+    Ok(())
+}
+
+// x-goog-meta-* headers
+fn metadata(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((
+                name.as_str().strip_prefix("x-goog-meta-")?.to_owned(),
+                value.to_str().ok()?.to_owned(),
+            ))
+        })
+        .collect()
+}
+
+// the inclusive byte range requested by a Range header
+fn range(headers: &HeaderMap, len: usize) -> Result<Option<(usize, usize)>, Error> {
+    let Some((first, last)) = header(headers, "range")
+        .and_then(|value| value.strip_prefix("bytes="))
+        .and_then(|value| value.split_once('-'))
+    else {
+        return Ok(None);
+    };
+    let (first, last) = match (first.parse::<usize>(), last.parse::<usize>()) {
+        (Ok(first), Ok(last)) if first <= last => (first, last),
+        (Ok(first), Err(_)) if last.is_empty() => (first, usize::MAX),
+        (Err(_), Ok(suffix)) if first.is_empty() && suffix > 0 => {
+            (len.saturating_sub(suffix), usize::MAX)
+        }
+        // malformed ranges are ignored
+        _ => return Ok(None),
+    };
+    if first >= len {
+        Err(Error::InvalidRange)
+    } else {
+        Ok(Some((first, last.min(len - 1))))
+    }
+}
+
+// the body of a download, honoring Range
+fn download(
+    builder: http::response::Builder,
+    headers: &HeaderMap,
+    object: &Object,
+    head: bool,
+) -> Result<Response, Error> {
+    let len = object.data.len();
+    let (builder, body) = match range(headers, len)? {
+        Some((first, last)) => (
+            builder.status(StatusCode::PARTIAL_CONTENT).header(
+                http::header::CONTENT_RANGE,
+                format!("bytes {first}-{last}/{len}"),
+            ),
+            object.data.slice(first..=last),
+        ),
+        None => (builder, object.data.clone()),
+    };
+    let builder = builder
+        .header(http::header::ACCEPT_RANGES, "bytes")
+        .header(http::header::CONTENT_LENGTH, body.len())
+        .header(http::header::CONTENT_TYPE, object.content_type());
+    if head {
+        builder.body(Full::default())
+    } else {
+        builder.body(Full::new(body))
+    }
+    .map_err(internal)
+}
+
+// a response that could not be built, which validation on write should rule out
+fn internal(e: http::Error) -> Error {
+    Error::Internal(e.to_string())
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn decode(value: &str) -> String {
+    percent_encoding::percent_decode_str(value)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+fn rfc3339(time: OffsetDateTime) -> String {
+    time.format(&time::format_description::well_known::Rfc3339)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,345 @@
+// the on-disk layout of a persisted fake
+//
+// {root}/{bucket}/{object}                                 data of the live version
+// {root}/{bucket}/.gcs-local/bucket.json                   bucket settings
+// {root}/{bucket}/.gcs-local/objects/{object}.json         generations and metadata of every version
+// {root}/{bucket}/.gcs-local/objects/{object}.{generation} data of a noncurrent version
+//
+// object names are percent-encoded so that each object is a single file. files without a
+// sidecar are picked up as live objects, so a directory of plain files can be served as is.
+//
+// every file is written to a temporary file next to it and renamed into place, data before the
+// sidecar, so that a reader or a crash sees either the old or the new version of an object.
+
+use super::{Attributes, Bucket, Object};
+use bytes::Bytes;
+use percent_encoding::{AsciiSet, CONTROLS};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use time::OffsetDateTime;
+
+const META: &str = ".gcs-local";
+
+const ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b'"')
+    .add(b'%')
+    .add(b'*')
+    .add(b'/')
+    .add(b':')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'|');
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BucketSettings {
+    versioning: bool,
+}
+
+#[serde_with::serde_as]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Version {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    component_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde_as(as = "Option<serde_with::base64::Base64>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    customer_key_sha256: Option<[u8; 32]>,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    generation: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kms_key_name: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    metageneration: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    time_created: OffsetDateTime,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    time_deleted: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    updated: OffsetDateTime,
+}
+
+pub(super) fn load(root: &Path) -> io::Result<BTreeMap<String, Bucket>> {
+    let mut buckets = BTreeMap::new();
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(bucket_name) = entry.file_name().to_str() {
+            if !bucket_name.starts_with('.') {
+                buckets.insert(bucket_name.to_owned(), load_bucket(&entry.path())?);
+            }
+        }
+    }
+    Ok(buckets)
+}
+
+fn load_bucket(path: &Path) -> io::Result<Bucket> {
+    let meta = path.join(META);
+    let settings = match fs::read(meta.join("bucket.json")) {
+        Ok(value) => serde_json::from_slice(&value).map_err(io::Error::other)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => BucketSettings::default(),
+        Err(e) => return Err(e),
+    };
+    let mut objects = BTreeMap::new();
+
+    let sidecars = match fs::read_dir(meta.join("objects")) {
+        Ok(entries) => entries.collect::<io::Result<Vec<_>>>()?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    for entry in sidecars {
+        let file_name = entry.file_name();
+        let Some(encoded) = file_name
+            .to_str()
+            .and_then(|name| name.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        let name = decode(encoded);
+        let versions = serde_json::from_slice::<Vec<Version>>(&fs::read(entry.path())?)
+            .map_err(io::Error::other)?;
+        let count = versions.len();
+        let mut loaded = Vec::with_capacity(count);
+        for (i, version) in versions.into_iter().enumerate() {
+            let data_path = if i + 1 == count && version.time_deleted.is_none() {
+                path.join(encoded)
+            } else {
+                meta.join("objects")
+                    .join(format!("{encoded}.{}", version.generation))
+            };
+            // versions whose data was removed by hand are dropped
+            let data = match fs::read(data_path) {
+                Ok(data) => Bytes::from(data),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            loaded.push(Object {
+                name: name.clone(),
+                attributes: Attributes {
+                    content_type: version.content_type,
+                    customer_key_sha256: version.customer_key_sha256,
+                    kms_key_name: version.kms_key_name,
+                    metadata: version.metadata,
+                },
+                component_count: version.component_count,
+                data,
+                generation: version.generation,
+                metageneration: version.metageneration,
+                time_created: version.time_created,
+                time_deleted: version.time_deleted,
+                updated: version.updated,
+            });
+        }
+        if !loaded.is_empty() {
+            objects.insert(name, loaded);
+        }
+    }
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(encoded) = file_name.to_str() else {
+            continue;
+        };
+        if encoded.starts_with('.') || !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = decode(encoded);
+        if objects
+            .get(&name)
+            .and_then(|versions: &Vec<Object>| versions.last())
+            .is_some_and(Object::is_live)
+        {
+            continue;
+        }
+        let modified = OffsetDateTime::from(entry.metadata()?.modified()?);
+        let object = Object {
+            name: name.clone(),
+            attributes: Attributes::default(),
+            component_count: None,
+            data: Bytes::from(fs::read(entry.path())?),
+            generation: (modified.unix_timestamp_nanos() / 1000) as u64,
+            metageneration: 1,
+            time_created: modified,
+            time_deleted: None,
+            updated: modified,
+        };
+        objects.entry(name).or_default().push(object);
+    }
+
+    Ok(Bucket {
+        objects,
+        versioning: settings.versioning,
+    })
+}
+
+pub(super) fn store_bucket(root: &Path, bucket_name: &str, bucket: &Bucket) -> io::Result<()> {
+    let meta = root.join(bucket_name).join(META);
+    fs::create_dir_all(meta.join("objects"))?;
+    let settings = BucketSettings {
+        versioning: bucket.versioning,
+    };
+    write(
+        &meta.join("bucket.json"),
+        serde_json::to_vec_pretty(&settings).map_err(io::Error::other)?,
+    )
+}
+
+pub(super) fn store_object(
+    root: &Path,
+    bucket_name: &str,
+    object_name: &str,
+    versions: &[Object],
+) -> io::Result<()> {
+    let path = root.join(bucket_name);
+    let objects = path.join(META).join("objects");
+    fs::create_dir_all(&objects)?;
+    let encoded = encode(object_name);
+    let live = versions.last().filter(|object| object.is_live());
+    let noncurrent = &versions[..versions.len() - usize::from(live.is_some())];
+
+    for entry in fs::read_dir(&objects)? {
+        let entry = entry?;
+        let generation = entry.file_name().to_str().and_then(|name| {
+            name.strip_prefix(&encoded)?
+                .strip_prefix('.')?
+                .parse::<u64>()
+                .ok()
+        });
+        if let Some(generation) = generation {
+            if !noncurrent
+                .iter()
+                .any(|object| object.generation == generation)
+            {
+                fs::remove_file(entry.path())?;
+            }
+        }
+    }
+    for object in noncurrent {
+        let data_path = objects.join(format!("{encoded}.{}", object.generation));
+        if !data_path.exists() {
+            write(&data_path, &object.data)?;
+        }
+    }
+    match live {
+        Some(object) => write(&path.join(&encoded), &object.data)?,
+        None => remove_file(&path.join(&encoded))?,
+    }
+
+    let sidecar = objects.join(format!("{encoded}.json"));
+    if versions.is_empty() {
+        remove_file(&sidecar)
+    } else {
+        let versions = versions
+            .iter()
+            .map(|object| Version {
+                component_count: object.component_count,
+                content_type: object.attributes.content_type.clone(),
+                customer_key_sha256: object.attributes.customer_key_sha256,
+                generation: object.generation,
+                kms_key_name: object.attributes.kms_key_name.clone(),
+                metadata: object.attributes.metadata.clone(),
+                metageneration: object.metageneration,
+                time_created: object.time_created,
+                time_deleted: object.time_deleted,
+                updated: object.updated,
+            })
+            .collect::<Vec<_>>();
+        write(
+            &sidecar,
+            serde_json::to_vec_pretty(&versions).map_err(io::Error::other)?,
+        )
+    }
+}
+
+// the temporary file starts with a dot, which encoded object names never do, so a leftover one is
+// not loaded as an object
+fn write<D>(path: &Path, data: D) -> io::Result<()>
+where
+    D: AsRef<[u8]>,
+{
+    let file_name = path
+        .file_name()
+        .and_then(OsStr::to_str)
+        .ok_or_else(|| io::Error::other(format!("invalid path: {}", path.display())))?;
+    let temp = path.with_file_name(format!(".{file_name}.tmp"));
+    let result = fs::File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(data.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// a leading dot is encoded as well to keep object files apart from the metadata directory
+fn encode(object_name: &str) -> String {
+    let encoded = percent_encoding::utf8_percent_encode(object_name, ENCODE_SET).to_string();
+    match encoded.strip_prefix('.') {
+        Some(rest) => format!("%2E{rest}"),
+        None => encoded,
+    }
+}
+
+fn decode(encoded: &str) -> String {
+    super::decode(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_write() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&root).unwrap();
+        let path = root.join("a%2Fb");
+        for data in [b"old", b"new"] {
+            super::write(&path, data).unwrap();
+            assert_eq!(std::fs::read(&path).unwrap(), data);
+        }
+        // nothing is left behind next to the file
+        let names = std::fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a%2Fb"]);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_encode() {
+        for (object_name, encoded) in [
+            ("a/b c.txt", "a%2Fb c.txt"),
+            (".gcs-local", "%2Egcs-local"),
+            ("100%", "100%25"),
+            ("日本語", "%E6%97%A5%E6%9C%AC%E8%AA%9E"),
+        ] {
+            assert_eq!(super::encode(object_name), encoded);
+            assert_eq!(super::decode(encoded), object_name);
+        }
+    }
+}
@@ -0,0 +1,529 @@
+// https://cloud.google.com/storage/docs/json_api
+
+use super::{
+    Attributes, Error, ListOptions, Object, Preconditions, Request, Response, Session, State,
+    Upload,
+};
+use crate::api::json::{compose_object, list_objects, rewrite_object};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use bytes::Bytes;
+use http::{Method, StatusCode};
+use http_body_util::Full;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+pub(super) fn handle(state: &mut State, request: Request) -> Response {
+    let path = request.path.clone();
+    let segments = path
+        .split('/')
+        .skip(1)
+        .map(super::decode)
+        .collect::<Vec<_>>();
+    let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+    let method = request.method.clone();
+    let result = match (&method, &segments[..]) {
+        (&Method::GET, ["storage", "v1", "b", bucket_name, "o"]) => {
+            list_objects(state, &request, bucket_name)
+        }
+        (&Method::GET, ["storage", "v1", "b", bucket_name, "o", object_name]) => {
+            get_object(state, &request, bucket_name, object_name)
+        }
+        (&Method::PATCH, ["storage", "v1", "b", bucket_name, "o", object_name]) => {
+            patch_object(state, &request, bucket_name, object_name)
+        }
+        (&Method::DELETE, ["storage", "v1", "b", bucket_name, "o", object_name]) => {
+            delete_object(state, &request, bucket_name, object_name)
+        }
+        (&Method::POST, ["storage", "v1", "b", bucket_name, "o", object_name, "compose"]) => {
+            compose_object(state, &request, bucket_name, object_name)
+        }
+        (
+            &Method::POST,
+            ["storage", "v1", "b", source_bucket_name, "o", source_object_name, "rewriteTo", "b", bucket_name, "o", object_name],
+        ) => rewrite_object(
+            state,
+            &request,
+            (source_bucket_name, source_object_name),
+            (bucket_name, object_name),
+        ),
+        (&Method::POST, ["upload", "storage", "v1", "b", bucket_name, "o"]) => {
+            insert_object(state, &request, bucket_name)
+        }
+        (&Method::PUT | &Method::DELETE, ["upload", "storage", "v1", "b", _, "o"]) => {
+            upload(state, &request)
+        }
+        _ => Err(Error::NotImplemented),
+    };
+    result
+        .and_then(|response| state.commit(response))
+        .unwrap_or_else(error)
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/status-codes
+pub(super) fn error(e: Error) -> Response {
+    let status = e.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return http::Response::builder()
+            .status(status)
+            .body(Full::default())
+            .unwrap();
+    }
+    let reason = match e {
+        Error::BadDigest | Error::InvalidArgument(_) => "invalid",
+        Error::CustomerEncryptionKeyMismatch => "customerEncryptionKeySha256Mismatch",
+        Error::Internal(_) => "backendError",
+        Error::InvalidRange => "requestedRangeNotSatisfiable",
+        Error::NoSuchBucket(_) | Error::NoSuchKey(..) | Error::NoSuchUpload => "notFound",
+        Error::NotImplemented => "notImplemented",
+        Error::NotModified => "notModified",
+        Error::PreconditionFailed => "conditionNotMet",
+        Error::ResourceIsEncryptedWithCustomerEncryptionKey => {
+            "resourceIsEncryptedWithCustomerEncryptionKey"
+        }
+    };
+    let message = e.message();
+    let body = json!({
+        "error": {
+            "code": status.as_u16(),
+            "message": message,
+            "errors": [{
+                "domain": "global",
+                "reason": reason,
+                "message": message,
+            }],
+        },
+    });
+    json_response(status, &body)
+}
+
+fn json_response<T>(status: StatusCode, body: &T) -> Response
+where
+    T: Serialize,
+{
+    http::Response::builder()
+        .status(status)
+        .header(
+            http::header::CONTENT_TYPE,
+            "application/json; charset=UTF-8",
+        )
+        .body(Full::new(Bytes::from(serde_json::to_vec(body).unwrap())))
+        .unwrap()
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects#resource
+fn resource(bucket_name: &str, object: &Object) -> crate::object::Object {
+    crate::object::Object {
+        bucket: bucket_name.to_owned(),
+        component_count: object.component_count,
+        content_type: object
+            .content_type()
+            .parse()
+            .unwrap_or(mime::APPLICATION_OCTET_STREAM),
+        crc32c: object.crc32c(),
+        customer_encryption: object.attributes.customer_key_sha256.map(|key_sha256| {
+            crate::encryption::CustomerEncryption {
+                encryption_algorithm: crate::encryption::ALGORITHM.to_owned(),
+                key_sha256,
+            }
+        }),
+        generation: object.generation,
+        hard_delete_time: None,
+        id: format!("{bucket_name}/{}/{}", object.name, object.generation),
+        kms_key_name: object.attributes.kms_key_name.clone(),
+        md5_hash: object.md5(),
+        metadata: object.attributes.metadata.clone(),
+        metageneration: object.metageneration,
+        name: object.name.clone(),
+        size: object.data.len() as u64,
+        soft_delete_time: None,
+        storage_class: crate::bucket::StorageClass::Standard,
+        time_created: object.time_created,
+        time_deleted: object.time_deleted,
+        updated: object.updated,
+    }
+}
+
+fn generation(request: &Request, key: &str) -> Result<Option<u64>, Error> {
+    request
+        .query
+        .get(key)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| Error::InvalidArgument(format!("Invalid {key}: {value}")))
+        })
+        .transpose()
+}
+
+fn body(request: &Request) -> Result<Map<String, Value>, Error> {
+    if request.body.is_empty() {
+        return Ok(Map::new());
+    }
+    serde_json::from_slice(&request.body)
+        .map_err(|e| Error::InvalidArgument(format!("Invalid JSON payload: {e}")))
+}
+
+// contentType and metadata of an object resource in a request body
+fn attributes(body: &Map<String, Value>, attributes: &mut Attributes) {
+    if let Some(content_type) = body.get("contentType").and_then(Value::as_str) {
+        attributes.content_type = Some(content_type.to_owned());
+    }
+    if let Some(metadata) = body.get("metadata").and_then(Value::as_object) {
+        for (key, value) in metadata {
+            match value.as_str() {
+                Some(value) => {
+                    attributes.metadata.insert(key.clone(), value.to_owned());
+                }
+                None => {
+                    attributes.metadata.remove(key);
+                }
+            }
+        }
+    }
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects/list
+fn list_objects(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+) -> Result<Response, Error> {
+    let start_after = request
+        .query
+        .get("pageToken")
+        .map(|page_token| {
+            BASE64_STANDARD
+                .decode(page_token)
+                .ok()
+                .and_then(|value| String::from_utf8(value).ok())
+                .ok_or_else(|| Error::InvalidArgument(format!("Invalid pageToken: {page_token}")))
+        })
+        .transpose()?;
+    let options = ListOptions {
+        delimiter: request.query.get("delimiter").cloned(),
+        max_results: request
+            .query
+            .get("maxResults")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(1000),
+        prefix: request.query.get("prefix").cloned().unwrap_or_default(),
+        start_after,
+        versions: request.query.get("versions").map(String::as_str) == Some("true"),
+    };
+    let list = state.list(bucket_name, &options)?;
+    let body = list_objects::Response {
+        items: list
+            .objects
+            .iter()
+            .map(|object| resource(bucket_name, object))
+            .collect(),
+        next_page_token: list.next.map(|next| BASE64_STANDARD.encode(next)),
+        prefixes: list.prefixes,
+    };
+    Ok(json_response(StatusCode::OK, &body))
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects/get
+fn get_object(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_query(&request.query)?;
+    let generation = generation(request, "generation")?;
+    let media = request.query.get("alt").map(String::as_str) == Some("media");
+    let customer_key_sha256 = super::customer_key_sha256(&request.headers, "x-goog-encryption")?;
+    let object = state.get(bucket_name, object_name, generation)?;
+    preconditions.check(Some(object), true)?;
+    if media {
+        object.check_customer_key(customer_key_sha256)?;
+        let builder = http::Response::builder()
+            .header("x-goog-generation", object.generation)
+            .header("x-goog-metageneration", object.metageneration)
+            .header("x-goog-hash", object.hashes().collect::<Vec<_>>().join(","));
+        super::download(builder, &request.headers, object, false)
+    } else {
+        Ok(json_response(
+            StatusCode::OK,
+            &resource(bucket_name, object),
+        ))
+    }
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects/patch
+fn patch_object(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_query(&request.query)?;
+    let generation = generation(request, "generation")?;
+    let body = body(request)?;
+    let object = state.get(bucket_name, object_name, generation)?;
+    preconditions.check(Some(object), false)?;
+    let mut attributes = object.attributes.clone();
+    self::attributes(&body, &mut attributes);
+    attributes.check()?;
+    object.attributes = attributes;
+    object.metageneration += 1;
+    object.updated = time::OffsetDateTime::now_utc();
+    let resource = resource(bucket_name, object);
+    state.touch(bucket_name, object_name);
+    Ok(json_response(StatusCode::OK, &resource))
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects/delete
+fn delete_object(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_query(&request.query)?;
+    let generation = generation(request, "generation")?;
+    state.delete(bucket_name, object_name, generation, preconditions)?;
+    Ok(http::Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Full::default())
+        .unwrap())
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects/compose
+fn compose_object(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_query(&request.query)?;
+    let customer_key_sha256 = super::customer_key_sha256(&request.headers, "x-goog-encryption")?;
+    let body = serde_json::from_slice::<compose_object::Request>(&request.body)
+        .map_err(|e| Error::InvalidArgument(format!("Invalid JSON payload: {e}")))?;
+    if body.source_objects.is_empty() || body.source_objects.len() > 32 {
+        return Err(Error::InvalidArgument(
+            "The number of source components provided must be between 1 and 32.".to_owned(),
+        ));
+    }
+
+    let mut data = Vec::new();
+    let mut component_count = 0;
+    for source_object in &body.source_objects {
+        let object = state.get(bucket_name, &source_object.name, source_object.generation)?;
+        if let Some(object_preconditions) = &source_object.object_preconditions {
+            if object_preconditions.if_generation_match != object.generation {
+                return Err(Error::PreconditionFailed);
+            }
+        }
+        object.check_customer_key(customer_key_sha256)?;
+        data.extend_from_slice(&object.data);
+        component_count += object.component_count.unwrap_or(1);
+    }
+
+    let attributes = Attributes {
+        content_type: body
+            .destination
+            .content_type
+            .map(|content_type| content_type.to_string()),
+        customer_key_sha256,
+        kms_key_name: request.query.get("kmsKeyName").cloned(),
+        ..Attributes::default()
+    };
+    let object = state.insert(
+        bucket_name,
+        object_name,
+        attributes,
+        Bytes::from(data),
+        Some(component_count),
+        preconditions,
+    )?;
+    Ok(json_response(
+        StatusCode::OK,
+        &resource(bucket_name, &object),
+    ))
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects/rewrite
+// the progress so far is carried in the rewrite token as a byte count
+fn rewrite_object(
+    state: &mut State,
+    request: &Request,
+    (source_bucket_name, source_object_name): (&str, &str),
+    (bucket_name, object_name): (&str, &str),
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_query(&request.query)?;
+    let source_generation = generation(request, "sourceGeneration")?;
+    let max_bytes_rewritten_per_call = generation(request, "maxBytesRewrittenPerCall")?;
+    let rewritten = request
+        .query
+        .get("rewriteToken")
+        .map(|rewrite_token| {
+            rewrite_token.parse::<u64>().map_err(|_| {
+                Error::InvalidArgument(format!("Invalid rewriteToken: {rewrite_token}"))
+            })
+        })
+        .transpose()?
+        .unwrap_or(0);
+    let source_customer_key_sha256 =
+        super::customer_key_sha256(&request.headers, "x-goog-copy-source-encryption")?;
+    let customer_key_sha256 = super::customer_key_sha256(&request.headers, "x-goog-encryption")?;
+    let body = body(request)?;
+
+    let source = state.get(source_bucket_name, source_object_name, source_generation)?;
+    source.check_customer_key(source_customer_key_sha256)?;
+    let object_size = source.data.len() as u64;
+    let total_bytes_rewritten = max_bytes_rewritten_per_call
+        .map_or(object_size, |max| rewritten.saturating_add(max))
+        .min(object_size);
+    if total_bytes_rewritten < object_size {
+        let body = rewrite_object::Response {
+            done: false,
+            object_size,
+            resource: None,
+            rewrite_token: Some(total_bytes_rewritten.to_string()),
+            total_bytes_rewritten,
+        };
+        return Ok(json_response(StatusCode::OK, &body));
+    }
+
+    let mut attributes = source.attributes.clone();
+    self::attributes(&body, &mut attributes);
+    let object = state.copy(
+        source_bucket_name,
+        source_object_name,
+        source_generation,
+        source_customer_key_sha256,
+        bucket_name,
+        object_name,
+        Some(attributes),
+        Attributes {
+            customer_key_sha256,
+            kms_key_name: request.query.get("destinationKmsKeyName").cloned(),
+            ..Attributes::default()
+        },
+        preconditions,
+    )?;
+    let body = rewrite_object::Response {
+        done: true,
+        object_size,
+        resource: Some(resource(bucket_name, &object)),
+        rewrite_token: None,
+        total_bytes_rewritten: object_size,
+    };
+    Ok(json_response(StatusCode::OK, &body))
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/objects/insert
+fn insert_object(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+) -> Result<Response, Error> {
+    if let Some(upload_id) = request.query.get("upload_id") {
+        return upload_response(state, request, bucket_name, upload_id);
+    }
+    let preconditions = Preconditions::from_query(&request.query)?;
+    let upload_type = request.query.get("uploadType").map(String::as_str);
+    let mut attributes = Attributes {
+        customer_key_sha256: super::customer_key_sha256(&request.headers, "x-goog-encryption")?,
+        kms_key_name: request.query.get("kmsKeyName").cloned(),
+        ..Attributes::default()
+    };
+    match upload_type {
+        Some("media") => {
+            attributes.content_type =
+                super::header(&request.headers, "content-type").map(str::to_owned);
+            let object_name = request
+                .query
+                .get("name")
+                .ok_or_else(|| Error::InvalidArgument("Required: name".to_owned()))?;
+            super::check_hashes(&request.headers, &request.body)?;
+            let object = state.insert(
+                bucket_name,
+                object_name,
+                attributes,
+                request.body.clone(),
+                None,
+                preconditions,
+            )?;
+            Ok(json_response(
+                StatusCode::OK,
+                &resource(bucket_name, &object),
+            ))
+        }
+        Some("resumable") => {
+            let body = body(request)?;
+            self::attributes(&body, &mut attributes);
+            if let Some(content_type) = super::header(&request.headers, "x-upload-content-type") {
+                attributes.content_type = Some(content_type.to_owned());
+            }
+            let object_name = body
+                .get("name")
+                .and_then(Value::as_str)
+                .or(request.query.get("name").map(String::as_str))
+                .ok_or_else(|| Error::InvalidArgument("Required: name".to_owned()))?;
+            let upload_id = state.start_session(Session {
+                bucket_name: bucket_name.to_owned(),
+                object_name: object_name.to_owned(),
+                attributes,
+                data: Vec::new(),
+                preconditions,
+            })?;
+            http::Response::builder()
+                .header(
+                    http::header::LOCATION,
+                    format!(
+                        "{}/upload/storage/v1/b/{bucket_name}/o?uploadType=resumable&upload_id={upload_id}",
+                        request.origin,
+                    ),
+                )
+                .body(Full::default())
+                .map_err(super::internal)
+        }
+        _ => Err(Error::NotImplemented),
+    }
+}
+
+// https://cloud.google.com/storage/docs/performing-resumable-uploads
+fn upload(state: &mut State, request: &Request) -> Result<Response, Error> {
+    let upload_id = request
+        .query
+        .get("upload_id")
+        .ok_or_else(|| Error::InvalidArgument("Required: upload_id".to_owned()))?;
+    let bucket_name = state
+        .sessions
+        .get(upload_id)
+        .map(|session| session.bucket_name.clone())
+        .ok_or(Error::NoSuchUpload)?;
+    upload_response(state, request, &bucket_name, upload_id)
+}
+
+fn upload_response(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    upload_id: &str,
+) -> Result<Response, Error> {
+    match state.upload(request, upload_id)? {
+        // https://cloud.google.com/storage/docs/json_api/v1/status-codes#499_Client_Closed_Request
+        Upload::Cancelled => Ok(http::Response::builder()
+            .status(499)
+            .body(Full::default())
+            .unwrap()),
+        Upload::Complete(object) => Ok(json_response(
+            StatusCode::OK,
+            &resource(bucket_name, &object),
+        )),
+        Upload::Incomplete(persisted) => Ok(incomplete(persisted)),
+    }
+}
+
+// 308 Resume Incomplete, with the persisted range if any
+pub(super) fn incomplete(persisted: usize) -> Response {
+    let mut builder = http::Response::builder().status(StatusCode::PERMANENT_REDIRECT);
+    if persisted > 0 {
+        builder = builder.header(http::header::RANGE, format!("bytes=0-{}", persisted - 1));
+    }
+    builder.body(Full::default()).unwrap()
+}
@@ -0,0 +1,584 @@
+use crate::api;
+use bytes::Bytes;
+use headers::{ContentLength, HeaderMapExt};
+use http::StatusCode;
+use http_body_util::{BodyExt, Full};
+use tower::ServiceExt;
+
+type Body = Full<Bytes>;
+
+fn service() -> super::Service {
+    let service = super::Service::new();
+    service.create_bucket("bucket").unwrap();
+    service
+}
+
+fn body(data: &'static [u8]) -> Body {
+    Full::from(Bytes::from_static(data))
+}
+
+fn assert_status<S, B>(e: api::Error<S, B>, status: StatusCode) -> Bytes {
+    if let api::Error::Status(api::StatusError(e)) = e {
+        assert_eq!(e.status(), status);
+        e.into_body()
+    } else {
+        panic!("unexpected error")
+    }
+}
+
+async fn call(
+    service: &super::Service,
+    request: http::Request<Full<Bytes>>,
+) -> (http::response::Parts, Bytes) {
+    let (parts, body) = service.clone().oneshot(request).await.unwrap().into_parts();
+    (parts, body.collect().await.unwrap().to_bytes())
+}
+
+#[tokio::test]
+async fn test_xml_object() {
+    let service = service();
+    let data = b"hello world";
+
+    api::xml::put_object::builder("bucket", "a/b c", body(data))
+        .typed_header(crate::header::XGoogHash {
+            crc32c: Some(crc32c::crc32c(data).to_be_bytes()),
+            md5: None,
+        })
+        .send(service.clone())
+        .await
+        .unwrap();
+    {
+        let response = api::xml::get_object::builder("bucket", "a/b c")
+            .send::<_, Body, _>(service.clone())
+            .await
+            .unwrap();
+        let hash = response
+            .headers()
+            .typed_get::<crate::header::XGoogHash>()
+            .unwrap();
+        assert_eq!(hash.crc32c, Some(crc32c::crc32c(data).to_be_bytes()));
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, data.as_slice());
+    }
+    {
+        let response = api::xml::head_object::builder("bucket", "a/b c")
+            .send::<_, Body, _>(service.clone())
+            .await
+            .unwrap();
+        let ContentLength(content_length) = response.headers().typed_get().unwrap();
+        assert_eq!(content_length, data.len() as u64);
+    }
+    {
+        let e = api::xml::put_object::builder("bucket", "object", body(data))
+            .typed_header(crate::header::XGoogHash {
+                crc32c: None,
+                md5: Some([0; 16]),
+            })
+            .send(service.clone())
+            .await
+            .unwrap_err();
+        let body = assert_status(e, StatusCode::BAD_REQUEST);
+        assert!(std::str::from_utf8(&body)
+            .unwrap()
+            .contains("<Code>BadDigest</Code>"));
+    }
+
+    api::xml::delete_object::builder("bucket", "a/b c")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    {
+        let e = api::xml::get_object::builder("bucket", "a/b c")
+            .send::<_, Body, _>(service.clone())
+            .await
+            .unwrap_err();
+        let body = assert_status(e, StatusCode::NOT_FOUND);
+        assert_eq!(
+            body,
+            concat!(
+                "<?xml version='1.0' encoding='UTF-8'?>",
+                "<Error><Code>NoSuchKey</Code>",
+                "<Message>The specified key does not exist.</Message>",
+                "<Details>No such object: bucket/a/b c</Details>",
+                "</Error>",
+            ),
+        );
+    }
+    {
+        let e = api::xml::head_object::builder("missing", "object")
+            .send::<_, Body, _>(service.clone())
+            .await
+            .unwrap_err();
+        assert_status(e, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn test_versioning() {
+    let service = service();
+
+    api::xml::put_bucket_versioning::builder("bucket", crate::bucket::Versioning { enabled: true })
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    api::xml::put_object::builder("bucket", "object", body(b"v1"))
+        .send(service.clone())
+        .await
+        .unwrap();
+    api::xml::put_object::builder("bucket", "object", body(b"v2"))
+        .send(service.clone())
+        .await
+        .unwrap();
+
+    let response = api::xml::list_objects::builder("bucket")
+        .versions(true)
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    let versions = &response.body().version;
+    assert_eq!(
+        versions
+            .iter()
+            .map(|version| (version.size, version.is_latest))
+            .collect::<Vec<_>>(),
+        [(2, Some(true)), (2, Some(false))],
+    );
+
+    let noncurrent = versions[1].generation;
+    let response = api::xml::get_object::builder("bucket", "object")
+        .generation(noncurrent)
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "v1");
+
+    api::xml::delete_object::builder("bucket", "object")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    let response = api::xml::list_objects::builder("bucket")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    assert!(response.body().contents.is_empty());
+    let response = api::xml::list_objects::builder("bucket")
+        .versions(true)
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.body().version.len(), 2);
+}
+
+#[tokio::test]
+async fn test_list_objects() {
+    let service = service();
+    for object_name in ["a/1", "a/2", "b", "c/1", "d"] {
+        api::xml::put_object::builder("bucket", object_name, body(b""))
+            .send(service.clone())
+            .await
+            .unwrap();
+    }
+
+    let mut names = Vec::new();
+    let mut marker = None;
+    loop {
+        let mut builder = api::xml::list_objects::builder("bucket")
+            .delimiter("/")
+            .max_keys(2);
+        if let Some(marker) = marker.take() {
+            builder = builder.marker(marker);
+        }
+        let response = builder
+            .send::<_, Body, _>(service.clone())
+            .await
+            .unwrap()
+            .into_body();
+        names.extend(response.contents.into_iter().map(|contents| contents.key));
+        names.extend(
+            response
+                .common_prefixes
+                .into_iter()
+                .map(|prefixes| prefixes.prefix),
+        );
+        if !response.is_truncated {
+            break;
+        }
+        marker = response.next_marker;
+    }
+    names.sort();
+    assert_eq!(names, ["a/", "b", "c/", "d"]);
+
+    let json_service = service.clone();
+    let mut names = Vec::new();
+    let mut page_token = None;
+    loop {
+        let mut builder = api::json::list_objects::builder("bucket")
+            .prefix("a/")
+            .max_results(1);
+        if let Some(page_token) = page_token.take() {
+            builder = builder.page_token(page_token);
+        }
+        let response = builder
+            .send::<_, String, _>(json_service.clone())
+            .await
+            .unwrap()
+            .into_body();
+        names.extend(response.items.into_iter().map(|object| object.name));
+        match response.next_page_token {
+            Some(next_page_token) => page_token = Some(next_page_token),
+            None => break,
+        }
+    }
+    assert_eq!(names, ["a/1", "a/2"]);
+}
+
+#[tokio::test]
+async fn test_preconditions_and_ranges() {
+    let service = service();
+    let uri = "https://bucket.storage.googleapis.com/object";
+
+    let put = |generation: u64| {
+        http::Request::put(uri)
+            .header("x-goog-if-generation-match", generation)
+            .body(body(b"0123456789"))
+            .unwrap()
+    };
+    let (parts, _) = call(&service, put(0)).await;
+    assert_eq!(parts.status, StatusCode::OK);
+    let generation = parts.headers["x-goog-generation"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let (parts, body) = call(&service, put(0)).await;
+    assert_eq!(parts.status, StatusCode::PRECONDITION_FAILED);
+    assert!(std::str::from_utf8(&body)
+        .unwrap()
+        .contains("<Code>PreconditionFailed</Code>"));
+    let (parts, _) = call(&service, put(generation)).await;
+    assert_eq!(parts.status, StatusCode::OK);
+
+    for (range, status, expected) in [
+        ("bytes=2-4", StatusCode::PARTIAL_CONTENT, Some("234")),
+        ("bytes=7-", StatusCode::PARTIAL_CONTENT, Some("789")),
+        ("bytes=-2", StatusCode::PARTIAL_CONTENT, Some("89")),
+        ("bytes=8-100", StatusCode::PARTIAL_CONTENT, Some("89")),
+        ("bytes=10-", StatusCode::RANGE_NOT_SATISFIABLE, None),
+    ] {
+        let request = http::Request::get(uri)
+            .header(http::header::RANGE, range)
+            .body(Full::default())
+            .unwrap();
+        let (parts, body) = call(&service, request).await;
+        assert_eq!(parts.status, status);
+        if let Some(expected) = expected {
+            assert_eq!(body, expected);
+        }
+    }
+
+    let request = http::Request::get(
+        "https://storage.googleapis.com/storage/v1/b/bucket/o/object?ifGenerationMatch=1",
+    )
+    .body(Full::default())
+    .unwrap();
+    let (parts, body) = call(&service, request).await;
+    assert_eq!(parts.status, StatusCode::PRECONDITION_FAILED);
+    let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+    assert_eq!(body["error"]["code"], 412);
+    assert_eq!(body["error"]["errors"][0]["reason"], "conditionNotMet");
+}
+
+#[tokio::test]
+async fn test_invalid_attributes() {
+    let service = service();
+    let uri = "https://storage.googleapis.com/storage/v1/b/bucket/o/object";
+    api::json::insert_object::builder("bucket", "object", body(b"data"))
+        .send(service.clone())
+        .await
+        .unwrap();
+
+    // values that could not be returned as headers are rejected before they are stored
+    for attributes in [
+        serde_json::json!({"contentType": "text/plain\r\nx-injected: 1"}),
+        serde_json::json!({"metadata": {"bad key": "value"}}),
+        serde_json::json!({"metadata": {"key": "line\nbreak"}}),
+    ] {
+        let request = http::Request::patch(uri)
+            .body(Full::from(serde_json::to_vec(&attributes).unwrap()))
+            .unwrap();
+        let (parts, body) = call(&service, request).await;
+        assert_eq!(parts.status, StatusCode::BAD_REQUEST);
+        let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+        assert_eq!(body["error"]["errors"][0]["reason"], "invalid");
+    }
+    let request = http::Request::post(
+        "https://storage.googleapis.com/upload/storage/v1/b/bucket/o?uploadType=resumable",
+    )
+    .body(Full::from(
+        serde_json::to_vec(&serde_json::json!({"name": "other", "metadata": {"k": "\u{7f}"}}))
+            .unwrap(),
+    ))
+    .unwrap();
+    let (parts, _) = call(&service, request).await;
+    assert_eq!(parts.status, StatusCode::BAD_REQUEST);
+
+    // and the service keeps serving the object unchanged
+    let response = api::xml::head_object::builder("bucket", "object")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()[http::header::CONTENT_TYPE],
+        "application/octet-stream",
+    );
+    assert!(!response
+        .headers()
+        .keys()
+        .any(|name| name.as_str().starts_with("x-goog-meta-")));
+}
+
+#[tokio::test]
+async fn test_compose_and_rewrite() {
+    let service = service();
+    for (object_name, data) in [("a", b"hello "), ("b", b"world!")] {
+        api::json::insert_object::builder("bucket", object_name, body(data))
+            .send(service.clone())
+            .await
+            .unwrap();
+    }
+
+    let response = api::json::compose_object::builder(
+        "bucket",
+        "composite",
+        vec![
+            api::json::compose_object::SourceObject::new("a"),
+            api::json::compose_object::SourceObject::new("b"),
+        ],
+    )
+    .content_type(mime::TEXT_PLAIN)
+    .if_generation_match(0)
+    .send::<_, String, _>(service.clone())
+    .await
+    .unwrap();
+    let object = response.into_body();
+    assert_eq!(object.size, 12);
+    assert_eq!(object.content_type, mime::TEXT_PLAIN);
+    assert_eq!(object.md5_hash, None);
+
+    let response = api::json::rewrite_object::send_until_done::<_, String, _>(
+        api::json::rewrite_object::builder("bucket", "composite", "bucket", "copy")
+            .max_bytes_rewritten_per_call(5),
+        service.clone(),
+    )
+    .await
+    .unwrap();
+    let object = response.into_body();
+    assert_eq!(object.size, 12);
+    assert_eq!(object.crc32c, crc32c::crc32c(b"hello world!").to_be_bytes());
+
+    let response = api::xml::get_object::builder("bucket", "copy")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "hello world!");
+}
+
+#[tokio::test]
+async fn test_resumable_upload() {
+    let service = service();
+
+    let request = http::Request::post("https://bucket.storage.googleapis.com/object")
+        .header("x-goog-resumable", "start")
+        .header(http::header::CONTENT_TYPE, "text/plain")
+        .body(Full::default())
+        .unwrap();
+    let (parts, _) = call(&service, request).await;
+    assert_eq!(parts.status, StatusCode::CREATED);
+    let location = parts.headers[http::header::LOCATION]
+        .to_str()
+        .unwrap()
+        .to_owned();
+
+    let request = http::Request::put(&location)
+        .header(http::header::CONTENT_RANGE, "bytes 0-4/*")
+        .body(body(b"hello"))
+        .unwrap();
+    let (parts, _) = call(&service, request).await;
+    assert_eq!(parts.status, StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(parts.headers[http::header::RANGE], "bytes=0-4");
+
+    let request = http::Request::put(&location)
+        .header(http::header::CONTENT_RANGE, "bytes 5-10/11")
+        .body(body(b" world"))
+        .unwrap();
+    let (parts, _) = call(&service, request).await;
+    assert_eq!(parts.status, StatusCode::OK);
+
+    let response = api::json::get_object::builder("bucket", "object")
+        .send::<_, String, _>(service.clone())
+        .await
+        .unwrap();
+    let object = response.into_body();
+    assert_eq!(object.size, 11);
+    assert_eq!(object.content_type, mime::TEXT_PLAIN);
+
+    let request = http::Request::put(&location)
+        .header(http::header::CONTENT_RANGE, "bytes */11")
+        .body(Full::default())
+        .unwrap();
+    let (parts, _) = call(&service, request).await;
+    assert_eq!(parts.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_customer_encryption_key() {
+    let service = service();
+    let key = crate::encryption::CustomerEncryptionKey::new([1; 32]);
+    let new_key = crate::encryption::CustomerEncryptionKey::new([2; 32]);
+
+    api::xml::put_object::builder("bucket", "object", body(b"secret"))
+        .customer_encryption_key(key.clone())
+        .send(service.clone())
+        .await
+        .unwrap();
+    {
+        let e = api::xml::get_object::builder("bucket", "object")
+            .send::<_, Body, _>(service.clone())
+            .await
+            .unwrap_err();
+        let body = assert_status(e, StatusCode::BAD_REQUEST);
+        assert!(std::str::from_utf8(&body)
+            .unwrap()
+            .contains("<Code>ResourceIsEncryptedWithCustomerEncryptionKey</Code>"));
+    }
+    {
+        let e = api::xml::get_object::builder("bucket", "object")
+            .customer_encryption_key(new_key.clone())
+            .send::<_, Body, _>(service.clone())
+            .await
+            .unwrap_err();
+        assert_status(e, StatusCode::BAD_REQUEST);
+    }
+
+    api::xml::copy_object::builder("bucket", "copy", "bucket", "object")
+        .source_customer_encryption_key(key.clone())
+        .customer_encryption_key(new_key.clone())
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    let response = api::json::get_object::builder("bucket", "copy")
+        .send::<_, String, _>(service.clone())
+        .await
+        .unwrap();
+    let customer_encryption = response.into_body().customer_encryption.unwrap();
+    assert_eq!(customer_encryption.key_sha256, new_key.key_sha256());
+
+    let response = api::json::insert_object::builder("bucket", "kms", body(b""))
+        .kms_key_name("projects/p/locations/l/keyRings/r/cryptoKeys/k")
+        .send(service.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        response.into_body().kms_key_name.as_deref(),
+        Some("projects/p/locations/l/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1"),
+    );
+}
+
+#[tokio::test]
+async fn test_open() {
+    let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(root.join("bucket")).unwrap();
+    std::fs::write(root.join("bucket").join("plain.txt"), "plain").unwrap();
+
+    let service = super::Service::open(&root).unwrap();
+    api::xml::put_bucket_versioning::builder("bucket", crate::bucket::Versioning { enabled: true })
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    for data in [b"v1", b"v2"] {
+        api::xml::put_object::builder("bucket", "a/b", body(data))
+            .typed_header(headers::ContentType::text())
+            .send(service.clone())
+            .await
+            .unwrap();
+    }
+    drop(service);
+    assert_eq!(
+        std::fs::read(root.join("bucket").join("a%2Fb")).unwrap(),
+        b"v2"
+    );
+
+    let service = super::Service::open(&root).unwrap();
+    let response = api::json::list_objects::builder("bucket")
+        .versions(true)
+        .send::<_, String, _>(service.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        response
+            .into_body()
+            .items
+            .iter()
+            .map(|object| (
+                object.name.as_str(),
+                object.size,
+                object.time_deleted.is_some()
+            ))
+            .collect::<Vec<_>>(),
+        [("a/b", 2, false), ("a/b", 2, true), ("plain.txt", 5, false)],
+    );
+    let response = api::json::get_object::builder("bucket", "a/b")
+        .send::<_, String, _>(service.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.into_body().content_type, mime::TEXT_PLAIN);
+
+    api::xml::delete_object::builder("bucket", "plain.txt")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    assert!(!root.join("bucket").join("plain.txt").exists());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn test_open_rollback() {
+    let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(root.join("bucket")).unwrap();
+    let service = super::Service::open(&root).unwrap();
+
+    // a directory where the object file belongs makes the write fail
+    std::fs::create_dir(root.join("bucket").join("b")).unwrap();
+    let e = api::xml::put_object::builder("bucket", "b", body(b"b"))
+        .send(service.clone())
+        .await
+        .unwrap_err();
+    assert_status(e, StatusCode::INTERNAL_SERVER_ERROR);
+    let e = api::xml::get_object::builder("bucket", "b")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap_err();
+    assert_status(e, StatusCode::NOT_FOUND);
+
+    // the next write also cleans up after the failed one
+    std::fs::remove_dir(root.join("bucket").join("b")).unwrap();
+    api::xml::put_object::builder("bucket", "c", body(b"c"))
+        .send(service.clone())
+        .await
+        .unwrap();
+    let service = super::Service::open(&root).unwrap();
+    let e = api::xml::get_object::builder("bucket", "b")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap_err();
+    assert_status(e, StatusCode::NOT_FOUND);
+    api::xml::get_object::builder("bucket", "c")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+
+    std::fs::remove_dir_all(root).unwrap();
+}
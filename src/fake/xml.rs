@@ -0,0 +1,428 @@
+// https://cloud.google.com/storage/docs/xml-api/overview
+
+use super::{
+    Attributes, Error, ListOptions, Object, Preconditions, Request, Response, Session, State,
+    Upload,
+};
+use crate::api::xml::list_objects;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use bytes::Bytes;
+use headers::HeaderMapExt;
+use http::{Method, StatusCode};
+use http_body_util::Full;
+use quick_xml::escape::escape;
+use std::fmt::Write;
+
+pub(super) fn handle(
+    state: &mut State,
+    request: Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Response {
+    let head = request.method == Method::HEAD;
+    let result = if object_name.is_empty() {
+        match (&request.method, request.query.contains_key("versioning")) {
+            (&Method::GET, true) => get_bucket_versioning(state, bucket_name),
+            (&Method::PUT, true) => put_bucket_versioning(state, &request, bucket_name),
+            (&Method::GET, false) => list_objects(state, &request, bucket_name),
+            _ => Err(Error::NotImplemented),
+        }
+    } else {
+        match (
+            &request.method,
+            request.query.get("upload_id"),
+            super::header(&request.headers, "x-goog-copy-source"),
+        ) {
+            (&Method::GET | &Method::HEAD, None, None) => {
+                get_object(state, &request, bucket_name, object_name)
+            }
+            (&Method::PUT | &Method::DELETE, Some(upload_id), _) => {
+                upload(state, &request, upload_id)
+            }
+            (&Method::PUT, None, Some(copy_source)) => {
+                copy_object(state, &request, copy_source, bucket_name, object_name)
+            }
+            (&Method::PUT, None, None) => put_object(state, &request, bucket_name, object_name),
+            (&Method::POST, None, None)
+                if super::header(&request.headers, "x-goog-resumable") == Some("start") =>
+            {
+                start_upload(state, &request, bucket_name, object_name)
+            }
+            (&Method::DELETE, None, None) => {
+                delete_object(state, &request, bucket_name, object_name)
+            }
+            _ => Err(Error::NotImplemented),
+        }
+    };
+    result
+        .and_then(|response| state.commit(response))
+        .unwrap_or_else(|e| if head { head_error(e) } else { error(e) })
+}
+
+// https://cloud.google.com/storage/docs/xml-api/reference-status
+pub(super) fn error(e: Error) -> Response {
+    let status = e.status();
+    let code = match &e {
+        Error::BadDigest => "BadDigest",
+        Error::CustomerEncryptionKeyMismatch | Error::InvalidArgument(_) => "InvalidArgument",
+        Error::Internal(_) => "InternalError",
+        Error::InvalidRange => "InvalidRange",
+        Error::NoSuchBucket(_) => "NoSuchBucket",
+        Error::NoSuchKey(..) => "NoSuchKey",
+        Error::NoSuchUpload => "NoSuchUpload",
+        Error::NotImplemented => "NotImplemented",
+        Error::NotModified => return head_error(e),
+        Error::PreconditionFailed => "PreconditionFailed",
+        Error::ResourceIsEncryptedWithCustomerEncryptionKey => {
+            "ResourceIsEncryptedWithCustomerEncryptionKey"
+        }
+    };
+    let message = match &e {
+        Error::NoSuchKey(..) => "The specified key does not exist.".to_owned(),
+        _ => e.message(),
+    };
+    let mut body = format!(
+        "<?xml version='1.0' encoding='UTF-8'?><Error><Code>{code}</Code><Message>{}</Message>",
+        escape(&message),
+    );
+    match &e {
+        Error::NoSuchBucket(bucket_name) => {
+            write!(body, "<BucketName>{}</BucketName>", escape(bucket_name)).unwrap();
+        }
+        Error::NoSuchKey(bucket_name, object_name) => {
+            write!(
+                body,
+                "<Details>No such object: {}/{}</Details>",
+                escape(bucket_name),
+                escape(object_name),
+            )
+            .unwrap();
+        }
+        _ => (),
+    }
+    body.push_str("</Error>");
+    xml_response(status, body)
+}
+
+// responses to HEAD carry no body, errors included
+fn head_error(e: Error) -> Response {
+    http::Response::builder()
+        .status(e.status())
+        .body(Full::default())
+        .unwrap()
+}
+
+fn xml_response(status: StatusCode, body: String) -> Response {
+    http::Response::builder()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, "application/xml; charset=UTF-8")
+        .body(Full::new(Bytes::from(body)))
+        .unwrap()
+}
+
+fn empty_response(status: StatusCode) -> Response {
+    http::Response::builder()
+        .status(status)
+        .body(Full::default())
+        .unwrap()
+}
+
+// https://cloud.google.com/storage/docs/xml-api/reference-headers
+fn object_headers(builder: http::response::Builder, object: &Object) -> http::response::Builder {
+    let mut builder = builder
+        .header(http::header::ETAG, format!("\"{}\"", object.etag()))
+        .header("x-goog-generation", object.generation)
+        .header("x-goog-metageneration", object.metageneration)
+        .header("x-goog-storage-class", "STANDARD")
+        .header("x-goog-stored-content-encoding", "identity")
+        .header("x-goog-stored-content-length", object.data.len());
+    for hash in object.hashes() {
+        builder = builder.header("x-goog-hash", hash);
+    }
+    for (key, value) in &object.attributes.metadata {
+        builder = builder.header(format!("x-goog-meta-{key}"), value);
+    }
+    if let Some(key_sha256) = object.attributes.customer_key_sha256 {
+        builder = builder
+            .header("x-goog-encryption-algorithm", crate::encryption::ALGORITHM)
+            .header(
+                "x-goog-encryption-key-sha256",
+                BASE64_STANDARD.encode(key_sha256),
+            );
+    }
+    if let Some(kms_key_name) = &object.attributes.kms_key_name {
+        builder = builder.header("x-goog-encryption-kms-key-name", kms_key_name);
+    }
+    if let Some(headers) = builder.headers_mut() {
+        headers.typed_insert(headers::LastModified::from(std::time::SystemTime::from(
+            object.updated,
+        )));
+    }
+    builder
+}
+
+// virtual-hosted style for the real endpoint, path style otherwise
+fn uri(origin: &str, bucket_name: &str, object_name: &str) -> String {
+    let encode =
+        |value| percent_encoding::utf8_percent_encode(value, percent_encoding::NON_ALPHANUMERIC);
+    if origin.ends_with(".storage.googleapis.com") {
+        format!("{origin}/{}", encode(object_name))
+    } else {
+        format!("{origin}/{}/{}", encode(bucket_name), encode(object_name))
+    }
+}
+
+fn generation(request: &Request) -> Result<Option<u64>, Error> {
+    request
+        .query
+        .get("generation")
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| Error::InvalidArgument(format!("Invalid generation: {value}")))
+        })
+        .transpose()
+}
+
+// Content-Type and x-goog-meta-* headers of an upload
+fn attributes(request: &Request) -> Result<Attributes, Error> {
+    Ok(Attributes {
+        content_type: super::header(&request.headers, "content-type").map(str::to_owned),
+        customer_key_sha256: super::customer_key_sha256(&request.headers, "x-goog-encryption")?,
+        kms_key_name: super::header(&request.headers, "x-goog-encryption-kms-key-name")
+            .map(str::to_owned),
+        metadata: super::metadata(&request.headers),
+    })
+}
+
+// https://cloud.google.com/storage/docs/xml-api/get-bucket-versioning
+fn get_bucket_versioning(state: &mut State, bucket_name: &str) -> Result<Response, Error> {
+    let bucket = state.bucket(bucket_name)?;
+    let body = if bucket.versioning {
+        "<VersioningConfiguration><Status>Enabled</Status></VersioningConfiguration>"
+    } else {
+        "<VersioningConfiguration><Status>Suspended</Status></VersioningConfiguration>"
+    };
+    Ok(xml_response(StatusCode::OK, body.to_owned()))
+}
+
+// https://cloud.google.com/storage/docs/xml-api/put-bucket-versioning
+fn put_bucket_versioning(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+) -> Result<Response, Error> {
+    let body = std::str::from_utf8(&request.body)
+        .map_err(|_| Error::InvalidArgument("Invalid XML document".to_owned()))?;
+    state.bucket(bucket_name)?.versioning = body.contains("<Status>Enabled</Status>");
+    state.touch(bucket_name, "");
+    Ok(empty_response(StatusCode::OK))
+}
+
+// https://cloud.google.com/storage/docs/xml-api/get-bucket-list
+fn list_objects(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+) -> Result<Response, Error> {
+    let versions = request.query.contains_key("versions");
+    let options = ListOptions {
+        delimiter: request.query.get("delimiter").cloned(),
+        max_results: request
+            .query
+            .get("max-keys")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(1000),
+        prefix: request.query.get("prefix").cloned().unwrap_or_default(),
+        start_after: request.query.get("marker").cloned(),
+        versions,
+    };
+    let list = state.list(bucket_name, &options)?;
+
+    let contents = list
+        .objects
+        .iter()
+        .map(|object| list_objects::Contents {
+            key: object.name.clone(),
+            generation: object.generation,
+            meta_generation: object.metageneration,
+            is_latest: versions.then(|| object.is_live()),
+            last_modified: object.updated,
+            etag: format!("\"{}\"", object.etag()),
+            size: object.data.len() as u64,
+        })
+        .collect::<Vec<_>>();
+    let (root, contents, version) = if versions {
+        ("ListVersionsResult", Vec::new(), contents)
+    } else {
+        ("ListBucketResult", contents, Vec::new())
+    };
+    let result = list_objects::ListBucketResult {
+        name: bucket_name.to_owned(),
+        prefix: Some(options.prefix).filter(|prefix| !prefix.is_empty()),
+        marker: options.start_after,
+        is_truncated: list.next.is_some(),
+        next_marker: list.next,
+        contents,
+        version,
+        delete_marker: Vec::new(),
+        common_prefixes: list
+            .prefixes
+            .into_iter()
+            .map(|prefix| list_objects::CommonPrefixes { prefix })
+            .collect(),
+    };
+    let body = quick_xml::se::to_string_with_root(root, &result)
+        .map_err(|e| Error::Internal(e.to_string()))?;
+    let body = format!("<?xml version='1.0' encoding='UTF-8'?>{body}");
+    Ok(xml_response(StatusCode::OK, body))
+}
+
+// https://cloud.google.com/storage/docs/xml-api/get-object-download
+// https://cloud.google.com/storage/docs/xml-api/head-object
+fn get_object(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_headers(&request.headers)?;
+    let generation = generation(request)?;
+    let customer_key_sha256 = super::customer_key_sha256(&request.headers, "x-goog-encryption")?;
+    let object = state.get(bucket_name, object_name, generation)?;
+    preconditions.check(Some(object), true)?;
+    object.check_customer_key(customer_key_sha256)?;
+    super::download(
+        object_headers(http::Response::builder(), object),
+        &request.headers,
+        object,
+        request.method == Method::HEAD,
+    )
+}
+
+// https://cloud.google.com/storage/docs/xml-api/put-object-upload
+fn put_object(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_headers(&request.headers)?;
+    let attributes = attributes(request)?;
+    super::check_hashes(&request.headers, &request.body)?;
+    let object = state.insert(
+        bucket_name,
+        object_name,
+        attributes,
+        request.body.clone(),
+        None,
+        preconditions,
+    )?;
+    object_headers(http::Response::builder(), &object)
+        .body(Full::default())
+        .map_err(super::internal)
+}
+
+// https://cloud.google.com/storage/docs/xml-api/put-object-copy
+fn copy_object(
+    state: &mut State,
+    request: &Request,
+    copy_source: &str,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_headers(&request.headers)?;
+    let copy_source = copy_source.trim_start_matches('/');
+    let (source_bucket_name, source_object_name) = copy_source
+        .split_once('/')
+        .map(|(bucket_name, object_name)| (super::decode(bucket_name), super::decode(object_name)))
+        .ok_or_else(|| {
+            Error::InvalidArgument(format!("Invalid x-goog-copy-source: {copy_source}"))
+        })?;
+    let source_generation = super::header(&request.headers, "x-goog-copy-source-generation")
+        .map(|value| {
+            value.parse().map_err(|_| {
+                Error::InvalidArgument(format!("Invalid x-goog-copy-source-generation: {value}"))
+            })
+        })
+        .transpose()?;
+    let source_customer_key_sha256 =
+        super::customer_key_sha256(&request.headers, "x-goog-copy-source-encryption")?;
+    let attributes = attributes(request)?;
+    let replace = super::header(&request.headers, "x-goog-metadata-directive") == Some("REPLACE");
+
+    let object = state.copy(
+        &source_bucket_name,
+        &source_object_name,
+        source_generation,
+        source_customer_key_sha256,
+        bucket_name,
+        object_name,
+        replace.then(|| attributes.clone()),
+        attributes,
+        preconditions,
+    )?;
+    let body = format!(
+        "<?xml version='1.0' encoding='UTF-8'?><CopyObjectResult><LastModified>{}</LastModified><ETag>\"{}\"</ETag></CopyObjectResult>",
+        super::rfc3339(object.updated),
+        object.etag(),
+    );
+    let builder = object_headers(http::Response::builder(), &object)
+        .header(http::header::CONTENT_TYPE, "application/xml; charset=UTF-8");
+    builder
+        .body(Full::new(Bytes::from(body)))
+        .map_err(super::internal)
+}
+
+// https://cloud.google.com/storage/docs/xml-api/delete-object
+fn delete_object(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_headers(&request.headers)?;
+    let generation = generation(request)?;
+    state.delete(bucket_name, object_name, generation, preconditions)?;
+    Ok(empty_response(StatusCode::NO_CONTENT))
+}
+
+// https://cloud.google.com/storage/docs/xml-api/post-object-resumable
+fn start_upload(
+    state: &mut State,
+    request: &Request,
+    bucket_name: &str,
+    object_name: &str,
+) -> Result<Response, Error> {
+    let preconditions = Preconditions::from_headers(&request.headers)?;
+    let attributes = attributes(request)?;
+    let upload_id = state.start_session(Session {
+        bucket_name: bucket_name.to_owned(),
+        object_name: object_name.to_owned(),
+        attributes,
+        data: Vec::new(),
+        preconditions,
+    })?;
+    http::Response::builder()
+        .status(StatusCode::CREATED)
+        .header(
+            http::header::LOCATION,
+            format!(
+                "{}?upload_id={upload_id}",
+                uri(&request.origin, bucket_name, object_name),
+            ),
+        )
+        .body(Full::default())
+        .map_err(super::internal)
+}
+
+// https://cloud.google.com/storage/docs/xml-api/put-object-resumable
+fn upload(state: &mut State, request: &Request, upload_id: &str) -> Result<Response, Error> {
+    match state.upload(request, upload_id)? {
+        Upload::Cancelled => Ok(empty_response(StatusCode::NO_CONTENT)),
+        Upload::Complete(object) => object_headers(http::Response::builder(), &object)
+            .body(Full::default())
+            .map_err(super::internal),
+        Upload::Incomplete(persisted) => Ok(super::json::incomplete(persisted)),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::acl::PredefinedAcl;
 use base64::prelude::{Engine, BASE64_STANDARD};
 use http::{HeaderName, HeaderValue};
 
@@ -62,6 +63,106 @@ impl headers::Header for XGoogHash {
     }
 }
 
+// https://cloud.google.com/storage/docs/xml-api/reference-headers#xgoogacl
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct XGoogAcl(pub PredefinedAcl);
+
+impl headers::Header for XGoogAcl {
+    fn name() -> &'static HeaderName {
+        static NAME: HeaderName = HeaderName::from_static("x-goog-acl");
+        &NAME
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        [
+            PredefinedAcl::AuthenticatedRead,
+            PredefinedAcl::BucketOwnerFullControl,
+            PredefinedAcl::BucketOwnerRead,
+            PredefinedAcl::Private,
+            PredefinedAcl::ProjectPrivate,
+            PredefinedAcl::PublicRead,
+            PredefinedAcl::PublicReadWrite,
+        ]
+        .into_iter()
+        .find(|predefined_acl| value == predefined_acl.as_xml_str())
+        .map(Self)
+        .ok_or_else(headers::Error::invalid)
+    }
+
+    fn encode<E>(&self, values: &mut E)
+    where
+        E: Extend<HeaderValue>,
+    {
+        values.extend([HeaderValue::from_static(self.0.as_xml_str())]);
+    }
+}
+
+// https://cloud.google.com/storage/docs/xml-api/reference-headers#xgooggeneration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct XGoogGeneration(pub u64);
+
+impl headers::Header for XGoogGeneration {
+    fn name() -> &'static HeaderName {
+        static NAME: HeaderName = HeaderName::from_static("x-goog-generation");
+        &NAME
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        let value = value
+            .to_str()
+            .map_err(|_| headers::Error::invalid())?
+            .parse()
+            .map_err(|_| headers::Error::invalid())?;
+        Ok(Self(value))
+    }
+
+    fn encode<E>(&self, values: &mut E)
+    where
+        E: Extend<HeaderValue>,
+    {
+        values.extend([HeaderValue::from(self.0)]);
+    }
+}
+
+// https://cloud.google.com/storage/docs/xml-api/reference-headers#xgoogencryptionkmskeyname
+// responses carry the key version, i.e. .../cryptoKeys/{key}/cryptoKeyVersions/{version}
+#[derive(Clone, Debug, PartialEq)]
+pub struct XGoogEncryptionKmsKeyName(pub String);
+
+impl headers::Header for XGoogEncryptionKmsKeyName {
+    fn name() -> &'static HeaderName {
+        static NAME: HeaderName = HeaderName::from_static("x-goog-encryption-kms-key-name");
+        &NAME
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        let value = value.to_str().map_err(|_| headers::Error::invalid())?;
+        Ok(Self(value.to_owned()))
+    }
+
+    fn encode<E>(&self, values: &mut E)
+    where
+        E: Extend<HeaderValue>,
+    {
+        values.extend(HeaderValue::from_str(&self.0).ok());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use headers::HeaderMapExt;
@@ -134,4 +235,67 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_x_goog_acl() {
+        {
+            let mut headers = HeaderMap::new();
+            headers.append(
+                "x-goog-acl",
+                HeaderValue::from_static("bucket-owner-full-control"),
+            );
+            assert_eq!(
+                headers.typed_get(),
+                Some(super::XGoogAcl(
+                    crate::acl::PredefinedAcl::BucketOwnerFullControl
+                )),
+            );
+        }
+        {
+            let mut headers = HeaderMap::new();
+            headers.typed_insert(super::XGoogAcl(crate::acl::PredefinedAcl::PublicRead));
+            assert_eq!(
+                headers.get("x-goog-acl"),
+                Some(&HeaderValue::from_static("public-read")),
+            );
+        }
+    }
+
+    #[test]
+    fn test_x_goog_generation() {
+        {
+            let mut headers = HeaderMap::new();
+            headers.append(
+                "x-goog-generation",
+                HeaderValue::from_static("1360887759327000"),
+            );
+            assert_eq!(
+                headers.typed_get(),
+                Some(super::XGoogGeneration(1360887759327000)),
+            );
+        }
+        {
+            let mut headers = HeaderMap::new();
+            headers.typed_insert(super::XGoogGeneration(1360887759327000));
+            assert_eq!(
+                headers.get("x-goog-generation"),
+                Some(&HeaderValue::from_static("1360887759327000")),
+            );
+        }
+    }
+
+    #[test]
+    fn test_x_goog_encryption_kms_key_name() {
+        let name = "projects/my-project/locations/us/keyRings/my-ring/cryptoKeys/my-key";
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(super::XGoogEncryptionKmsKeyName(name.to_owned()));
+        assert_eq!(
+            headers.get("x-goog-encryption-kms-key-name"),
+            Some(&HeaderValue::from_static(name)),
+        );
+        assert_eq!(
+            headers.typed_get(),
+            Some(super::XGoogEncryptionKmsKeyName(name.to_owned())),
+        );
+    }
 }
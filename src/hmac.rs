@@ -0,0 +1,257 @@
+// https://cloud.google.com/storage/docs/authentication/signatures#signing-process
+// https://cloud.google.com/storage/docs/authentication/hmackeys
+
+use crate::signature;
+use ::hmac::{Hmac, Mac};
+use futures::future::{Either, MapErr};
+use futures::{FutureExt, TryFutureExt};
+use http::header::{HeaderValue, AUTHORIZATION, HOST};
+use http::Request;
+use sha2::Sha256;
+use std::fmt;
+use std::future::{self, Ready};
+use std::task::{Context, Poll};
+use time::OffsetDateTime;
+
+pub const ALGORITHM: &str = "GOOG4-HMAC-SHA256";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+#[derive(Clone)]
+pub struct Credentials {
+    pub access_id: String,
+    pub secret: String,
+}
+
+// leaves out the secret
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_id", &self.access_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignError {
+    #[error(transparent)]
+    InvalidHeaderValue(http::header::InvalidHeaderValue),
+    #[error("missing host")]
+    MissingHost,
+    #[error(transparent)]
+    ToStr(http::header::ToStrError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<S> {
+    #[error(transparent)]
+    Service(S),
+    #[error(transparent)]
+    Sign(SignError),
+}
+
+impl Credentials {
+    pub(crate) fn signing_key(&self, time: OffsetDateTime) -> Vec<u8> {
+        [
+            signature::date(time).as_str(),
+            signature::REGION,
+            signature::SERVICE,
+            signature::REQUEST_TYPE,
+        ]
+        .into_iter()
+        .fold(format!("GOOG4{}", self.secret).into_bytes(), |key, data| {
+            hmac_sha256(&key, data.as_bytes())
+        })
+    }
+
+    // adds host, x-goog-date, x-goog-content-sha256 and authorization headers
+    pub fn sign<B>(&self, request: &mut Request<B>, time: OffsetDateTime) -> Result<(), SignError> {
+        if !request.headers().contains_key(HOST) {
+            let host = request.uri().authority().ok_or(SignError::MissingHost)?;
+            let host =
+                HeaderValue::from_str(host.as_str()).map_err(SignError::InvalidHeaderValue)?;
+            request.headers_mut().insert(HOST, host);
+        }
+        request.headers_mut().insert(
+            "x-goog-date",
+            HeaderValue::from_str(&signature::timestamp(time))
+                .map_err(SignError::InvalidHeaderValue)?,
+        );
+        request
+            .headers_mut()
+            .entry("x-goog-content-sha256")
+            .or_insert(HeaderValue::from_static(UNSIGNED_PAYLOAD));
+        request.headers_mut().remove(AUTHORIZATION);
+
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| Ok((name.as_str(), value.to_str()?)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(SignError::ToStr)?;
+        let payload = request.headers()["x-goog-content-sha256"]
+            .to_str()
+            .map_err(SignError::ToStr)?;
+        let (canonical_headers, signed_headers) = signature::canonical_headers(headers);
+        let canonical_request = signature::canonical_request(
+            request.method(),
+            &signature::canonical_path(request.uri().path()),
+            &signature::canonical_query(signature::decode_query(
+                request.uri().query().unwrap_or_default(),
+            )),
+            &canonical_headers,
+            &signed_headers,
+            payload,
+        );
+        let string_to_sign = signature::string_to_sign(ALGORITHM, time, &canonical_request);
+        let signature = hex::encode(hmac_sha256(
+            &self.signing_key(time),
+            string_to_sign.as_bytes(),
+        ));
+
+        let authorization = format!(
+            "{ALGORITHM} Credential={}/{}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.access_id,
+            signature::scope(time),
+        );
+        request.headers_mut().insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&authorization).map_err(SignError::InvalidHeaderValue)?,
+        );
+        Ok(())
+    }
+}
+
+pub(crate) fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[derive(Clone)]
+pub struct Service<S> {
+    inner: S,
+    credentials: Credentials,
+}
+
+impl<S, B> tower::Service<Request<B>> for Service<S>
+where
+    S: tower::Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = Error<S::Error>;
+    type Future = Future<S, B>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Error::Service)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        match self
+            .credentials
+            .sign(&mut request, OffsetDateTime::now_utc())
+        {
+            Ok(()) => self
+                .inner
+                .call(request)
+                .map_err(Error::Service as _)
+                .left_future(),
+            Err(e) => future::ready(Err(Error::Sign(e))).right_future(),
+        }
+    }
+}
+
+pub type Future<S, B> = Either<
+    MapErr<
+        <S as tower::Service<Request<B>>>::Future,
+        fn(
+            <S as tower::Service<Request<B>>>::Error,
+        ) -> Error<<S as tower::Service<Request<B>>>::Error>,
+    >,
+    Ready<
+        Result<
+            <S as tower::Service<Request<B>>>::Response,
+            Error<<S as tower::Service<Request<B>>>::Error>,
+        >,
+    >,
+>;
+
+#[derive(Clone)]
+pub struct Layer {
+    credentials: Credentials,
+}
+
+impl<S> tower::Layer<S> for Layer {
+    type Service = Service<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Service {
+            inner,
+            credentials: self.credentials.clone(),
+        }
+    }
+}
+
+impl Layer {
+    pub fn new(credentials: Credentials) -> Self {
+        Self { credentials }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use std::convert::Infallible;
+    use std::future;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_debug() {
+        let credentials = super::Credentials {
+            access_id: "GOOG1EXAMPLE".to_owned(),
+            secret: "bGoa+V7g/yqDXvKRqq+JTFn4uQZbPiQJo4pf9RzJ".to_owned(),
+        };
+        assert_eq!(
+            format!("{credentials:?}"),
+            r#"Credentials { access_id: "GOOG1EXAMPLE", .. }"#,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sign() {
+        let credentials = super::Credentials {
+            access_id: "GOOG1EXAMPLE".to_owned(),
+            secret: "bGoa+V7g/yqDXvKRqq+JTFn4uQZbPiQJo4pf9RzJ".to_owned(),
+        };
+        let service = tower::service_fn(|mut request: http::Request<Full<Bytes>>| {
+            // the builder escapes every character of the object name but the signature is
+            // computed over the path as GCS sees it: "/logs/app-1_v2.~tmp%20x"
+            assert_eq!(
+                request.uri(),
+                "https://my-bucket.storage.googleapis.com/logs%2Fapp%2D1%5Fv2%2E%7Etmp%20x",
+            );
+            credentials
+                .sign(&mut request, datetime!(2019-02-01 09:00:00 UTC))
+                .unwrap();
+            assert_eq!(
+                request.headers()["host"],
+                "my-bucket.storage.googleapis.com"
+            );
+            assert_eq!(request.headers()["x-goog-date"], "20190201T090000Z");
+            // computed by hand following the signing process
+            assert_eq!(
+                request.headers()["authorization"],
+                "GOOG4-HMAC-SHA256 Credential=GOOG1EXAMPLE/20190201/auto/storage/goog4_request, \
+                 SignedHeaders=host;x-goog-content-sha256;x-goog-date, \
+                 Signature=809018339e3f170656a772121fb5b57e937b56cd01312d38341bf2c3f2f03eda",
+            );
+            future::ready(Ok::<_, Infallible>(http::Response::new(
+                Full::<Bytes>::default(),
+            )))
+        });
+        crate::api::xml::get_object::builder("my-bucket", "logs/app-1_v2.~tmp x")
+            .send::<_, Full<Bytes>, _>(service)
+            .await
+            .unwrap();
+    }
+}
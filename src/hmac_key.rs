@@ -0,0 +1,43 @@
+// https://cloud.google.com/storage/docs/json_api/v1/projects/hmacKeys#resource
+
+use std::fmt;
+use time::OffsetDateTime;
+
+#[derive(Clone, serde::Deserialize)]
+pub struct HmacKey {
+    pub metadata: HmacKeyMetadata,
+    // only returned on creation
+    pub secret: String,
+}
+
+// leaves out the secret
+impl fmt::Debug for HmacKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacKey")
+            .field("metadata", &self.metadata)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HmacKeyMetadata {
+    pub access_id: String,
+    pub etag: String,
+    pub id: String,
+    pub project_id: String,
+    pub service_account_email: String,
+    pub state: State,
+    #[serde(with = "time::serde::rfc3339")]
+    pub time_created: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum State {
+    Active,
+    Deleted,
+    Inactive,
+}
@@ -0,0 +1,173 @@
+// https://cloud.google.com/storage/docs/json_api/v1/buckets/getIamPolicy#response
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Policy {
+    #[serde(default)]
+    pub bindings: Vec<Binding>,
+    // sent back on update to detect concurrent modifications
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub resource_id: Option<String>,
+    // 3 is required to read or write conditional bindings
+    #[serde(default)]
+    pub version: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Binding {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Expr>,
+    // e.g. `user:liz@example.com`, `serviceAccount:sa@project.iam.gserviceaccount.com`
+    #[serde(default)]
+    pub members: Vec<String>,
+    // e.g. `roles/storage.objectViewer`
+    pub role: String,
+}
+
+// https://cloud.google.com/storage/docs/access-control/iam#conditions
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Expr {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub expression: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl Policy {
+    // adds `member` to the unconditional binding of `role`, returns false if already present
+    pub fn add_member(&mut self, role: &str, member: &str) -> bool {
+        if let Some(binding) = self
+            .bindings
+            .iter_mut()
+            .find(|binding| binding.role == role && binding.condition.is_none())
+        {
+            if binding.members.iter().any(|value| value == member) {
+                false
+            } else {
+                binding.members.push(member.to_owned());
+                true
+            }
+        } else {
+            self.bindings.push(Binding {
+                condition: None,
+                members: vec![member.to_owned()],
+                role: role.to_owned(),
+            });
+            true
+        }
+    }
+
+    // removes `member` from the unconditional binding of `role`, returns false if absent
+    pub fn remove_member(&mut self, role: &str, member: &str) -> bool {
+        let Some(i) = self
+            .bindings
+            .iter()
+            .position(|binding| binding.role == role && binding.condition.is_none())
+        else {
+            return false;
+        };
+        let binding = &mut self.bindings[i];
+        let len = binding.members.len();
+        binding.members.retain(|value| value != member);
+        let removed = binding.members.len() < len;
+        if binding.members.is_empty() {
+            self.bindings.remove(i);
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Binding, Expr, Policy};
+
+    #[test]
+    fn test_policy_add_remove_member() {
+        let condition = Expr {
+            expression: r#"resource.name.startsWith("projects/_/buckets/b/objects/a/")"#.to_owned(),
+            title: Some("prefix".to_owned()),
+            ..Expr::default()
+        };
+        let mut policy = Policy {
+            bindings: vec![Binding {
+                condition: Some(condition.clone()),
+                members: vec!["user:liz@example.com".to_owned()],
+                role: "roles/storage.objectViewer".to_owned(),
+            }],
+            etag: Some("CAE=".to_owned()),
+            resource_id: None,
+            version: 3,
+        };
+
+        assert!(policy.add_member("roles/storage.objectViewer", "user:liz@example.com"));
+        assert!(!policy.add_member("roles/storage.objectViewer", "user:liz@example.com"));
+        assert!(policy.add_member("roles/storage.objectViewer", "user:bob@example.com"));
+        assert_eq!(policy.bindings.len(), 2);
+        assert_eq!(
+            policy.bindings[1],
+            Binding {
+                condition: None,
+                members: vec![
+                    "user:liz@example.com".to_owned(),
+                    "user:bob@example.com".to_owned(),
+                ],
+                role: "roles/storage.objectViewer".to_owned(),
+            },
+        );
+
+        assert!(policy.remove_member("roles/storage.objectViewer", "user:liz@example.com"));
+        assert!(!policy.remove_member("roles/storage.objectViewer", "user:liz@example.com"));
+        assert!(policy.remove_member("roles/storage.objectViewer", "user:bob@example.com"));
+        assert_eq!(policy.bindings.len(), 1);
+        assert_eq!(policy.bindings[0].condition, Some(condition));
+    }
+
+    #[test]
+    fn test_policy_json() {
+        let value = serde_json::json!({
+            "kind": "storage#policy",
+            "resourceId": "projects/_/buckets/my-bucket",
+            "version": 3,
+            "etag": "CAE=",
+            "bindings": [
+                {
+                    "role": "roles/storage.objectViewer",
+                    "members": ["allUsers"],
+                    "condition": {
+                        "title": "public",
+                        "expression": "resource.name.startsWith(\"projects/_/buckets/my-bucket/objects/public/\")",
+                    },
+                },
+            ],
+        });
+        let policy = serde_json::from_value::<Policy>(value).unwrap();
+        assert_eq!(
+            policy.resource_id.as_deref(),
+            Some("projects/_/buckets/my-bucket"),
+        );
+        assert_eq!(
+            serde_json::to_value(&policy).unwrap(),
+            serde_json::json!({
+                "version": 3,
+                "etag": "CAE=",
+                "bindings": [
+                    {
+                        "role": "roles/storage.objectViewer",
+                        "members": ["allUsers"],
+                        "condition": {
+                            "title": "public",
+                            "expression": "resource.name.startsWith(\"projects/_/buckets/my-bucket/objects/public/\")",
+                        },
+                    },
+                ],
+            }),
+        );
+    }
+}
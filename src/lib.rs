@@ -1,3 +1,25 @@
+pub mod acl;
 pub mod api;
+pub mod auth;
+pub mod bucket;
+#[cfg(any(test, feature = "cassette"))]
+pub mod cassette;
+pub mod cloud_event;
+pub mod encryption;
+#[cfg(any(test, feature = "fake"))]
+pub mod fake;
 pub mod header;
+pub mod hmac;
+pub mod hmac_key;
+pub mod iam;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod notification;
+pub mod object;
+pub mod operation;
+pub mod post_policy;
+pub mod retry;
+pub mod signature;
+pub mod signed_url;
+#[cfg(feature = "yup-oauth2")]
 pub mod yup_oauth2;
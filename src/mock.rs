@@ -0,0 +1,314 @@
+// a tower::Service for unit tests that checks every request against a queue of expectations and
+// answers each one with a canned response
+//
+// let service = mock::Service::new();
+// service.expect(
+//     mock::Expectation::new(Method::PUT, "https://bucket.storage.googleapis.com/object")
+//         .typed_header(ContentType::text())
+//         .body("hello")
+//         .status(StatusCode::OK),
+// );
+// api::xml::put_object::builder("bucket", "object", ...).send(service.clone()).await?;
+// service.assert_done();
+//
+// a request that does not match the next expectation panics with a diff of the two.
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use headers::{Header, HeaderMapExt};
+use http::header::{HeaderName, HeaderValue};
+use http::{HeaderMap, Method, Request, Response, StatusCode, Uri};
+use http_body::Frame;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Empty, Full, StreamBody};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+pub type Body = UnsyncBoxBody<Bytes, Error>;
+
+// a canned error, see Expectation::error and Expectation::response_stream
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+#[error("{0}")]
+pub struct Error(pub String);
+
+#[derive(Clone, Default)]
+pub struct Service {
+    expectations: Arc<Mutex<VecDeque<Expectation>>>,
+    count: Arc<AtomicUsize>,
+}
+
+impl Service {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn expect(&self, expectation: Expectation) -> &Self {
+        self.expectations.lock().unwrap().push_back(expectation);
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_done(&self) {
+        let expectations = self.expectations.lock().unwrap();
+        if !expectations.is_empty() {
+            let mut message = format!("{} expected request(s) were not sent", expectations.len());
+            for expectation in expectations.iter() {
+                write!(message, "\n  {} {}", expectation.method, expectation.uri).unwrap();
+            }
+            panic!("{message}");
+        }
+    }
+}
+
+impl<B> tower::Service<Request<B>> for Service
+where
+    B: http_body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: std::fmt::Debug,
+{
+    type Response = Response<Body>;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let this = self.clone();
+        async move {
+            let (parts, body) = request.into_parts();
+            let body = body.collect().await.unwrap().to_bytes();
+
+            let index = this.count.fetch_add(1, Ordering::Relaxed);
+            let Some(expectation) = this.expectations.lock().unwrap().pop_front() else {
+                panic!(
+                    "unexpected request #{index}: {} {}\n{}",
+                    parts.method,
+                    parts.uri,
+                    text(&body),
+                );
+            };
+            if let Some(diff) = expectation.diff(&parts, &body) {
+                panic!("request #{index} does not match the expectation\n{diff}");
+            }
+            expectation.response
+        }
+        .boxed()
+    }
+}
+
+pub struct Expectation {
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+    response: Result<Response<Body>, Error>,
+}
+
+impl Expectation {
+    // answers with an empty 200 OK unless told otherwise
+    pub fn new<U>(method: Method, uri: U) -> Self
+    where
+        U: TryInto<Uri>,
+        U::Error: std::fmt::Debug,
+    {
+        Self {
+            method,
+            uri: uri.try_into().expect("invalid uri"),
+            headers: HeaderMap::new(),
+            body: None,
+            response: Ok(Response::new(
+                Empty::new().map_err(|e| match e {}).boxed_unsync(),
+            )),
+        }
+    }
+
+    fn diff(&self, parts: &http::request::Parts, body: &Bytes) -> Option<String> {
+        let mut diff = String::new();
+        if parts.method != self.method {
+            writeln!(diff, "method:\n- {}\n+ {}", self.method, parts.method).unwrap();
+        }
+        if parts.uri != self.uri {
+            writeln!(diff, "uri:\n- {}\n+ {}", self.uri, parts.uri).unwrap();
+        }
+        for name in self.headers.keys() {
+            let expected = self.headers.get_all(name).iter().collect::<Vec<_>>();
+            let actual = parts.headers.get_all(name).iter().collect::<Vec<_>>();
+            if expected != actual {
+                writeln!(diff, "header {name}:\n- {expected:?}\n+ {actual:?}").unwrap();
+            }
+        }
+        if let Some(expected) = &self.body {
+            if expected != body {
+                writeln!(diff, "body:").unwrap();
+                diff_lines(&mut diff, &text(expected), &text(body));
+            }
+        }
+        (!diff.is_empty()).then_some(diff)
+    }
+}
+
+// the request side. headers that are not mentioned are not checked, and neither is the body
+// unless Expectation::body is called.
+impl Expectation {
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: std::fmt::Debug,
+    {
+        self.headers.append(
+            name.try_into().expect("invalid header name"),
+            value.try_into().expect("invalid header value"),
+        );
+        self
+    }
+
+    pub fn typed_header<H>(mut self, header: H) -> Self
+    where
+        H: Header,
+    {
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(header);
+        for (name, value) in &headers {
+            self.headers.append(name, value.clone());
+        }
+        self
+    }
+
+    pub fn body<T>(mut self, body: T) -> Self
+    where
+        T: Into<Bytes>,
+    {
+        self.body = Some(body.into());
+        self
+    }
+
+    // the body is serialized the same way as api::json does, so field order matters
+    pub fn json<T>(self, value: &T) -> Self
+    where
+        T: serde::Serialize,
+    {
+        self.body(serde_json::to_string(value).expect("unserializable json"))
+    }
+}
+
+// the response side
+impl Expectation {
+    pub fn status(mut self, status: StatusCode) -> Self {
+        if let Ok(response) = &mut self.response {
+            *response.status_mut() = status;
+        }
+        self
+    }
+
+    pub fn response_header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: std::fmt::Debug,
+    {
+        if let Ok(response) = &mut self.response {
+            response.headers_mut().append(
+                name.try_into().expect("invalid header name"),
+                value.try_into().expect("invalid header value"),
+            );
+        }
+        self
+    }
+
+    pub fn typed_response_header<H>(mut self, header: H) -> Self
+    where
+        H: Header,
+    {
+        if let Ok(response) = &mut self.response {
+            response.headers_mut().typed_insert(header);
+        }
+        self
+    }
+
+    pub fn response_body<T>(mut self, body: T) -> Self
+    where
+        T: Into<Bytes>,
+    {
+        if let Ok(response) = &mut self.response {
+            *response.body_mut() = Full::new(body.into())
+                .map_err(|e| match e {})
+                .boxed_unsync();
+        }
+        self
+    }
+
+    pub fn response_json<T>(self, value: &T) -> Self
+    where
+        T: serde::Serialize,
+    {
+        self.response_header(http::header::CONTENT_TYPE, "application/json")
+            .response_body(serde_json::to_string(value).expect("unserializable json"))
+    }
+
+    // streams the chunks one frame at a time. an Err ends the body with that error.
+    pub fn response_stream<I>(mut self, chunks: I) -> Self
+    where
+        I: IntoIterator<Item = Result<Bytes, Error>>,
+        I::IntoIter: Send + 'static,
+    {
+        if let Ok(response) = &mut self.response {
+            let frames = futures::stream::iter(chunks).map(|chunk| chunk.map(Frame::data));
+            *response.body_mut() = StreamBody::new(frames).boxed_unsync();
+        }
+        self
+    }
+
+    // fails the call itself instead of answering
+    pub fn error(mut self, error: Error) -> Self {
+        self.response = Err(error);
+        self
+    }
+}
+
+fn text(body: &[u8]) -> String {
+    match std::str::from_utf8(body) {
+        Ok(value) => value.to_owned(),
+        Err(_) => format!("{body:02x?}"),
+    }
+}
+
+// writes the lines between the common prefix and suffix of expected and actual
+fn diff_lines(diff: &mut String, expected: &str, actual: &str) {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+    let prefix = expected
+        .iter()
+        .zip(&actual)
+        .take_while(|(e, a)| e == a)
+        .count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(e, a)| e == a)
+        .count();
+    for line in &expected[..prefix] {
+        writeln!(diff, "  {line}").unwrap();
+    }
+    for line in &expected[prefix..expected.len() - suffix] {
+        writeln!(diff, "- {line}").unwrap();
+    }
+    for line in &actual[prefix..actual.len() - suffix] {
+        writeln!(diff, "+ {line}").unwrap();
+    }
+    for line in &expected[expected.len() - suffix..] {
+        writeln!(diff, "  {line}").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests;
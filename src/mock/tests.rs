@@ -0,0 +1,261 @@
+use super::{Error, Expectation};
+use crate::api;
+use bytes::Bytes;
+use headers::ContentType;
+use http::{Method, StatusCode};
+use http_body_util::{BodyExt, Full};
+
+type Body = Full<Bytes>;
+
+fn body(data: &'static [u8]) -> Body {
+    Full::from(Bytes::from_static(data))
+}
+
+#[tokio::test]
+async fn test_xml_put_object() {
+    let service = super::Service::new();
+    let key = crate::encryption::CustomerEncryptionKey::new([1; 32]);
+    service.expect(
+        Expectation::new(
+            Method::PUT,
+            "https://bucket.storage.googleapis.com/a%2Fb%20c",
+        )
+        .typed_header(ContentType::text_utf8())
+        .typed_header(crate::header::XGoogHash {
+            crc32c: Some([0, 1, 2, 3]),
+            md5: None,
+        })
+        .header("x-goog-encryption-algorithm", "AES256")
+        .header(
+            "x-goog-encryption-key",
+            "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=",
+        )
+        .header(
+            "x-goog-encryption-key-sha256",
+            "cs1uhCLEB/ttCYaQ8RMLfe1+wvf14dML2dUh8BU2N5M=",
+        )
+        .body("hello"),
+    );
+
+    api::xml::put_object::builder("bucket", "a/b c", body(b"hello"))
+        .typed_header(ContentType::text_utf8())
+        .typed_header(crate::header::XGoogHash {
+            crc32c: Some([0, 1, 2, 3]),
+            md5: None,
+        })
+        .customer_encryption_key(key)
+        .send(service.clone())
+        .await
+        .unwrap();
+    service.assert_done();
+}
+
+#[tokio::test]
+async fn test_xml_list_objects() {
+    let service = super::Service::new();
+    service.expect(
+        Expectation::new(
+            Method::GET,
+            "https://bucket.storage.googleapis.com/?delimiter=%2F&max-keys=2&prefix=a%2F",
+        )
+        .response_body(concat!(
+            "<ListBucketResult>",
+            "<Name>bucket</Name>",
+            "<Prefix>a/</Prefix>",
+            "<IsTruncated>false</IsTruncated>",
+            "<CommonPrefixes><Prefix>a/b/</Prefix></CommonPrefixes>",
+            "</ListBucketResult>",
+        )),
+    );
+
+    let response = api::xml::list_objects::builder("bucket")
+        .prefix("a/")
+        .delimiter("/")
+        .max_keys(2)
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    let result = response.into_body();
+    assert_eq!(result.common_prefixes.len(), 1);
+    assert_eq!(result.common_prefixes[0].prefix, "a/b/");
+    service.assert_done();
+}
+
+#[tokio::test]
+async fn test_json_patch_object() {
+    let service = super::Service::new();
+    let request = api::json::patch_object::Request {
+        content_type: Some(mime::TEXT_PLAIN_UTF_8),
+    };
+    service.expect(
+        Expectation::new(
+            Method::PATCH,
+            "https://storage.googleapis.com/storage/v1/b/bucket/o/a%2Fb?predefinedAcl=private",
+        )
+        .body(r#"{"contentType":"text/plain; charset=utf-8"}"#)
+        .response_json(&serde_json::json!({
+            "bucket": "bucket",
+            "contentType": "text/plain; charset=utf-8",
+            "crc32c": "mnG7TA==",
+            "generation": "1",
+            "id": "bucket/a/b/1",
+            "md5Hash": "XUFAKrxLKna5cZ2REBfFkg==",
+            "metageneration": "2",
+            "name": "a/b",
+            "size": "5",
+            "storageClass": "STANDARD",
+            "timeCreated": "2025-01-01T00:00:00Z",
+            "updated": "2025-01-01T00:00:00Z",
+        })),
+    );
+
+    let response = api::json::patch_object::builder("bucket", "a/b", request)
+        .predefined_acl(crate::acl::PredefinedAcl::Private)
+        .send::<_, String, _>(service.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.into_body().metageneration, 2);
+    service.assert_done();
+}
+
+#[tokio::test]
+async fn test_json_insert_object() {
+    let service = super::Service::new();
+    service.expect(
+        Expectation::new(
+            Method::POST,
+            "https://storage.googleapis.com/upload/storage/v1/b/bucket/o\
+             ?uploadType=media&name=a%2Fb&kmsKeyName=projects%2Fp%2Flocations%2Fl%2FkeyRings%2Fr%2FcryptoKeys%2Fk",
+        )
+        .header(http::header::CONTENT_TYPE, "text/plain")
+        .body("hello")
+        .status(StatusCode::FORBIDDEN)
+        .response_body("denied"),
+    );
+
+    let e = api::json::insert_object::builder("bucket", "a/b", body(b"hello"))
+        .content_type(mime::TEXT_PLAIN)
+        .kms_key_name("projects/p/locations/l/keyRings/r/cryptoKeys/k")
+        .send(service.clone())
+        .await
+        .unwrap_err();
+    if let api::Error::Status(api::StatusError(e)) = e {
+        assert_eq!(e.status(), StatusCode::FORBIDDEN);
+    } else {
+        panic!("unexpected error");
+    }
+    service.assert_done();
+}
+
+#[tokio::test]
+async fn test_response_stream() {
+    let service = super::Service::new();
+    service.expect(
+        Expectation::new(Method::GET, "https://bucket.storage.googleapis.com/object")
+            .response_stream([Ok(Bytes::from("hello ")), Ok(Bytes::from("world"))]),
+    );
+    service.expect(
+        Expectation::new(Method::GET, "https://bucket.storage.googleapis.com/object")
+            .response_stream([
+                Ok(Bytes::from("hello ")),
+                Err(Error("connection reset".to_owned())),
+            ]),
+    );
+
+    let response = api::xml::get_object::builder("bucket", "object")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    let mut body = response.into_body();
+    let mut chunks = Vec::new();
+    while let Some(frame) = body.frame().await {
+        chunks.push(frame.unwrap().into_data().unwrap());
+    }
+    assert_eq!(chunks, ["hello ", "world"]);
+
+    let response = api::xml::get_object::builder("bucket", "object")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap();
+    let e = response.into_body().collect().await.unwrap_err();
+    assert_eq!(e, Error("connection reset".to_owned()));
+    service.assert_done();
+}
+
+#[tokio::test]
+async fn test_error() {
+    let service = super::Service::new();
+    service.expect(
+        Expectation::new(
+            Method::DELETE,
+            "https://bucket.storage.googleapis.com/object",
+        )
+        .error(Error("timed out".to_owned())),
+    );
+
+    let e = api::xml::delete_object::builder("bucket", "object")
+        .send::<_, Body, _>(service.clone())
+        .await
+        .unwrap_err();
+    if let api::Error::Service(e) = e {
+        assert_eq!(e, Error("timed out".to_owned()));
+    } else {
+        panic!("unexpected error");
+    }
+    service.assert_done();
+}
+
+#[test]
+fn test_diff() {
+    let expectation = Expectation::new(Method::PUT, "https://bucket.storage.googleapis.com/a")
+        .header("x-goog-meta-a", "1")
+        .body("a\nb\nc\nd");
+    let (parts, ()) = http::Request::put("https://bucket.storage.googleapis.com/b")
+        .header("x-goog-meta-a", "2")
+        .header("x-goog-meta-b", "3")
+        .body(())
+        .unwrap()
+        .into_parts();
+    assert_eq!(
+        expectation
+            .diff(&parts, &Bytes::from("a\nx\ny\nd"))
+            .unwrap(),
+        concat!(
+            "uri:\n",
+            "- https://bucket.storage.googleapis.com/a\n",
+            "+ https://bucket.storage.googleapis.com/b\n",
+            "header x-goog-meta-a:\n",
+            "- [\"1\"]\n",
+            "+ [\"2\"]\n",
+            "body:\n",
+            "  a\n",
+            "- b\n",
+            "- c\n",
+            "+ x\n",
+            "+ y\n",
+            "  d\n",
+        ),
+    );
+    assert!(expectation
+        .diff(
+            &http::Request::put("https://bucket.storage.googleapis.com/a")
+                .header("x-goog-meta-a", "1")
+                .body(())
+                .unwrap()
+                .into_parts()
+                .0,
+            &Bytes::from("a\nb\nc\nd"),
+        )
+        .is_none());
+}
+
+#[tokio::test]
+#[should_panic(expected = "1 expected request(s) were not sent")]
+async fn test_assert_done() {
+    let service = super::Service::new();
+    service.expect(Expectation::new(
+        Method::GET,
+        "https://bucket.storage.googleapis.com/object",
+    ));
+    service.assert_done();
+}
@@ -0,0 +1,238 @@
+// https://cloud.google.com/storage/docs/pubsub-notifications
+
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+// https://cloud.google.com/storage/docs/json_api/v1/notifications#resource
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_attributes: HashMap<String, String>,
+    #[serde(default, skip_serializing)]
+    pub etag: Option<String>,
+    // all event types if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_types: Vec<EventType>,
+    #[serde(default, skip_serializing)]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_name_prefix: Option<String>,
+    pub payload_format: PayloadFormat,
+    // projects/{project}/topics/{topic}
+    pub topic: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EventType {
+    ObjectFinalize,
+    ObjectMetadataUpdate,
+    ObjectDelete,
+    ObjectArchive,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayloadFormat {
+    #[default]
+    JsonApiV1,
+    None,
+}
+
+// https://cloud.google.com/pubsub/docs/push#receive_push
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct PushRequest {
+    pub message: Message,
+    pub subscription: String,
+}
+
+// https://cloud.google.com/pubsub/docs/reference/rest/v1/PubsubMessage
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+    #[serde_as(as = "serde_with::base64::Base64")]
+    #[serde(default)]
+    pub data: Vec<u8>,
+    pub message_id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub publish_time: OffsetDateTime,
+}
+
+// https://cloud.google.com/storage/docs/pubsub-notifications#attributes
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub bucket_id: String,
+    pub event_time: OffsetDateTime,
+    pub event_type: EventType,
+    // projects/_/buckets/{bucket}/notificationConfigs/{id}
+    pub notification_config: String,
+    pub object_generation: u64,
+    pub object_id: String,
+    // the payload, if the payload format is JSON_API_V1
+    pub object: Option<crate::object::Object>,
+    // set on OBJECT_FINALIZE events that replaced a live object
+    pub overwrote_generation: Option<u64>,
+    // set on OBJECT_DELETE and OBJECT_ARCHIVE events caused by an overwrite
+    pub overwritten_by_generation: Option<u64>,
+    pub payload_format: PayloadFormat,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid attribute: {0}")]
+    InvalidAttribute(&'static str),
+    #[error(transparent)]
+    Json(serde_json::Error),
+    #[error("missing attribute: {0}")]
+    MissingAttribute(&'static str),
+}
+
+impl Event {
+    pub fn from_message(attributes: &HashMap<String, String>, data: &[u8]) -> Result<Self, Error> {
+        fn get<'a>(
+            attributes: &'a HashMap<String, String>,
+            key: &'static str,
+        ) -> Result<&'a str, Error> {
+            attributes
+                .get(key)
+                .map(String::as_str)
+                .ok_or(Error::MissingAttribute(key))
+        }
+        fn parse<T>(attributes: &HashMap<String, String>, key: &'static str) -> Result<T, Error>
+        where
+            T: for<'de> serde::Deserialize<'de>,
+        {
+            T::deserialize(
+                serde::de::value::StrDeserializer::<serde::de::value::Error>::new(get(
+                    attributes, key,
+                )?),
+            )
+            .map_err(|_| Error::InvalidAttribute(key))
+        }
+        fn parse_u64(
+            attributes: &HashMap<String, String>,
+            key: &'static str,
+        ) -> Result<u64, Error> {
+            get(attributes, key)?
+                .parse()
+                .map_err(|_| Error::InvalidAttribute(key))
+        }
+
+        let payload_format = parse(attributes, "payloadFormat")?;
+        let object = match payload_format {
+            PayloadFormat::JsonApiV1 => Some(serde_json::from_slice(data).map_err(Error::Json)?),
+            PayloadFormat::None => None,
+        };
+        Ok(Self {
+            bucket_id: get(attributes, "bucketId")?.to_owned(),
+            event_time: OffsetDateTime::parse(
+                get(attributes, "eventTime")?,
+                &time::format_description::well_known::Rfc3339,
+            )
+            .map_err(|_| Error::InvalidAttribute("eventTime"))?,
+            event_type: parse(attributes, "eventType")?,
+            notification_config: get(attributes, "notificationConfig")?.to_owned(),
+            object_generation: parse_u64(attributes, "objectGeneration")?,
+            object_id: get(attributes, "objectId")?.to_owned(),
+            object,
+            overwrote_generation: attributes
+                .contains_key("overwroteGeneration")
+                .then(|| parse_u64(attributes, "overwroteGeneration"))
+                .transpose()?,
+            overwritten_by_generation: attributes
+                .contains_key("overwrittenByGeneration")
+                .then(|| parse_u64(attributes, "overwrittenByGeneration"))
+                .transpose()?,
+            payload_format,
+        })
+    }
+}
+
+impl TryFrom<&Message> for Event {
+    type Error = Error;
+
+    fn try_from(value: &Message) -> Result<Self, Self::Error> {
+        Self::from_message(&value.attributes, &value.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventType, PayloadFormat};
+    use time::macros::datetime;
+
+    #[test]
+    fn test_push_request() {
+        let payload = serde_json::json!({
+            "kind": "storage#object",
+            "id": "my-bucket/logs/app.log/1700000000000000",
+            "name": "logs/app.log",
+            "bucket": "my-bucket",
+            "generation": "1700000000000000",
+            "metageneration": "1",
+            "contentType": "text/plain",
+            "timeCreated": "2023-11-14T22:13:20.000Z",
+            "updated": "2023-11-14T22:13:20.000Z",
+            "storageClass": "STANDARD",
+            "size": "11",
+            "md5Hash": "XrY7u+Ae7tCTyyK7j1rNww==",
+            "crc32c": "yZRlqg==",
+        });
+        let value = serde_json::json!({
+            "message": {
+                "attributes": {
+                    "notificationConfig": "projects/_/buckets/my-bucket/notificationConfigs/1",
+                    "eventType": "OBJECT_FINALIZE",
+                    "payloadFormat": "JSON_API_V1",
+                    "bucketId": "my-bucket",
+                    "objectId": "logs/app.log",
+                    "objectGeneration": "1700000000000000",
+                    "eventTime": "2023-11-14T22:13:20.000000Z",
+                    "overwroteGeneration": "1690000000000000",
+                },
+                "data": base64::Engine::encode(
+                    &base64::prelude::BASE64_STANDARD,
+                    payload.to_string(),
+                ),
+                "messageId": "1234567890",
+                "publishTime": "2023-11-14T22:13:20.5Z",
+            },
+            "subscription": "projects/my-project/subscriptions/my-subscription",
+        });
+        let request = serde_json::from_value::<super::PushRequest>(value).unwrap();
+        let event = super::Event::try_from(&request.message).unwrap();
+        assert_eq!(event.bucket_id, "my-bucket");
+        assert_eq!(event.event_time, datetime!(2023-11-14 22:13:20 UTC));
+        assert_eq!(event.event_type, EventType::ObjectFinalize);
+        assert_eq!(event.object_generation, 1700000000000000);
+        assert_eq!(event.object_id, "logs/app.log");
+        assert_eq!(event.overwrote_generation, Some(1690000000000000));
+        assert_eq!(event.overwritten_by_generation, None);
+        assert_eq!(event.payload_format, PayloadFormat::JsonApiV1);
+        let object = event.object.unwrap();
+        assert_eq!(object.name, "logs/app.log");
+        assert_eq!(object.size, 11);
+    }
+
+    #[test]
+    fn test_event_missing_attribute() {
+        let attributes = [
+            ("eventType", "OBJECT_DELETE"),
+            ("payloadFormat", "NONE"),
+            ("bucketId", "my-bucket"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect();
+        assert!(matches!(
+            super::Event::from_message(&attributes, &[]),
+            Err(super::Error::MissingAttribute("eventTime")),
+        ));
+    }
+}
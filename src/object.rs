@@ -0,0 +1,50 @@
+// https://cloud.google.com/storage/docs/json_api/v1/objects#resource
+
+use crate::bucket::StorageClass;
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Object {
+    pub bucket: String,
+    // set on composite objects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component_count: Option<u32>,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub content_type: mime::Mime,
+    #[serde_as(as = "serde_with::base64::Base64")]
+    pub crc32c: [u8; 4],
+    // set on objects encrypted with a customer-supplied key
+    #[serde(default)]
+    pub customer_encryption: Option<crate::encryption::CustomerEncryption>,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub generation: u64,
+    // set on soft-deleted objects
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub hard_delete_time: Option<OffsetDateTime>,
+    pub id: String,
+    // set on objects encrypted with a Cloud KMS key, including the key version
+    pub kms_key_name: Option<String>,
+    #[serde_as(as = "Option<serde_with::base64::Base64>")]
+    pub md5_hash: Option<[u8; 16]>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub metageneration: u64,
+    pub name: String,
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub size: u64,
+    // set on soft-deleted objects
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub soft_delete_time: Option<OffsetDateTime>,
+    pub storage_class: StorageClass,
+    #[serde(with = "time::serde::rfc3339")]
+    pub time_created: OffsetDateTime,
+    // set on noncurrent versions
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub time_deleted: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
@@ -0,0 +1,29 @@
+// https://cloud.google.com/storage/docs/json_api/v1/operations#resource
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    #[serde(default)]
+    pub done: bool,
+    pub error: Option<Status>,
+    pub metadata: Option<serde_json::Value>,
+    // projects/_/buckets/{bucket}/operations/{operationId}
+    pub name: String,
+    pub response: Option<serde_json::Value>,
+}
+
+impl Operation {
+    pub fn id(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+// https://cloud.google.com/storage/docs/json_api/v1/operations#status
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct Status {
+    pub code: i32,
+    #[serde(default)]
+    pub details: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub message: String,
+}
@@ -0,0 +1,481 @@
+// https://cloud.google.com/storage/docs/authentication/signatures#policy-document
+// https://cloud.google.com/storage/docs/xml-api/post-object-forms
+
+use crate::hmac::Credentials;
+use crate::signature::{self, ServiceAccountKey, Signer};
+use crate::signed_url::{UrlStyle, MAX_EXPIRATION};
+use http::uri::Scheme;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+pub fn builder<B, O>(bucket_name: B, object_name: O) -> Builder
+where
+    B: Into<String>,
+    O: Into<String>,
+{
+    Builder {
+        bucket_name: bucket_name.into(),
+        object_name: object_name.into(),
+        conditions: Vec::new(),
+        expiration: Duration::from_secs(60 * 60),
+        fields: Vec::new(),
+        scheme: Scheme::HTTPS,
+        time: None,
+        url_style: UrlStyle::PathStyle,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    // ["content-length-range", min, max]
+    ContentLengthRange(u64, u64),
+    // {field: value}
+    Eq(String, String),
+    // ["starts-with", "$field", prefix]
+    StartsWith(String, String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<E = rsa::Error> {
+    #[error("expiration exceeds 7 days: {0:?}")]
+    Expiration(Duration),
+    // time + expiration cannot be written as an RFC 3339 timestamp, e.g. after the year 9999
+    #[error("expiration time out of range")]
+    ExpirationTime,
+    #[error(transparent)]
+    Signer(E),
+}
+
+// the form must be posted to url with fields followed by the file field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PostPolicy {
+    pub url: String,
+    pub fields: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Builder {
+    bucket_name: String,
+    object_name: String,
+    conditions: Vec<Condition>,
+    expiration: Duration,
+    fields: Vec<(String, String)>,
+    scheme: Scheme,
+    time: Option<OffsetDateTime>,
+    url_style: UrlStyle,
+}
+
+struct Prepared {
+    url: String,
+    fields: Vec<(String, String)>,
+    // the base64-encoded policy document
+    policy: String,
+}
+
+impl Builder {
+    pub fn sign_with_key(self, key: &ServiceAccountKey) -> Result<PostPolicy, Error> {
+        let prepared = self.prepare(signature::RSA_ALGORITHM, &key.client_email)?;
+        let signature = key
+            .sign(prepared.policy.as_bytes())
+            .map_err(Error::Signer)?;
+        Ok(prepared.finish(&signature))
+    }
+
+    // for signers without a local key, e.g. IamSigner
+    pub async fn sign<G>(self, signer: &G) -> Result<PostPolicy, Error<G::Error>>
+    where
+        G: Signer,
+    {
+        let prepared = self.prepare(signature::RSA_ALGORITHM, signer.client_email())?;
+        let signature = signer
+            .sign(prepared.policy.as_bytes())
+            .await
+            .map_err(Error::Signer)?;
+        Ok(prepared.finish(&signature))
+    }
+
+    pub fn sign_with_hmac(self, credentials: &Credentials) -> Result<PostPolicy, Error> {
+        let time = self.time.unwrap_or_else(OffsetDateTime::now_utc);
+        let prepared = self
+            .time(time)
+            .prepare(crate::hmac::ALGORITHM, &credentials.access_id)?;
+        let signature =
+            crate::hmac::hmac_sha256(&credentials.signing_key(time), prepared.policy.as_bytes());
+        Ok(prepared.finish(&signature))
+    }
+
+    fn prepare<E>(self, algorithm: &str, credential: &str) -> Result<Prepared, Error<E>> {
+        let Self {
+            bucket_name,
+            object_name,
+            mut conditions,
+            expiration,
+            fields,
+            scheme,
+            time,
+            url_style,
+        } = self;
+        if expiration > MAX_EXPIRATION {
+            return Err(Error::Expiration(expiration));
+        }
+        let time = time.unwrap_or_else(OffsetDateTime::now_utc);
+
+        let key_starts_with = conditions.iter().any(
+            |condition| matches!(condition, Condition::StartsWith(field, _) if field == "key"),
+        );
+        let fields = [
+            ("key", object_name),
+            ("x-goog-algorithm", algorithm.to_owned()),
+            (
+                "x-goog-credential",
+                format!("{credential}/{}", signature::scope(time)),
+            ),
+            ("x-goog-date", signature::timestamp(time)),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_owned(), value))
+        .chain(fields)
+        .collect::<Vec<_>>();
+        conditions.push(Condition::Eq("bucket".to_owned(), bucket_name.clone()));
+        conditions.extend(
+            fields
+                .iter()
+                .filter(|(name, _)| !(key_starts_with && name == "key"))
+                .map(|(name, value)| Condition::Eq(name.clone(), value.clone())),
+        );
+
+        let expiration_time = time
+            .checked_add(time::Duration::try_from(expiration).map_err(|_| Error::ExpirationTime)?)
+            .ok_or(Error::ExpirationTime)?
+            .to_offset(time::UtcOffset::UTC)
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|_| Error::ExpirationTime)?;
+        let document = serde_json::json!({
+            "conditions": conditions.iter().map(Condition::to_json).collect::<Vec<_>>(),
+            "expiration": expiration_time,
+        });
+        let policy = base64::Engine::encode(
+            &base64::prelude::BASE64_STANDARD,
+            escape_non_ascii(&document.to_string()),
+        );
+
+        let (host, path) = url_style.host_and_path(&bucket_name, "");
+        Ok(Prepared {
+            url: format!("{scheme}://{host}{path}"),
+            fields,
+            policy,
+        })
+    }
+}
+
+impl Builder {
+    pub fn condition(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn content_length_range(self, min: u64, max: u64) -> Self {
+        self.condition(Condition::ContentLengthRange(min, max))
+    }
+
+    pub fn content_type(self, content_type: &mime::Mime) -> Self {
+        self.field("Content-Type", content_type.as_ref())
+    }
+
+    pub fn expiration(mut self, expiration: Duration) -> Self {
+        self.expiration = expiration;
+        self
+    }
+
+    // the value is also added as an exact-match condition
+    pub fn field<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+
+    // x-goog-meta-{key}
+    pub fn metadata<K, V>(self, key: K, value: V) -> Self
+    where
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        self.field(format!("x-goog-meta-{}", key.as_ref()), value)
+    }
+
+    pub fn scheme(mut self, scheme: Scheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    // field without the leading '$', e.g. "key"
+    pub fn starts_with<F, P>(self, field: F, prefix: P) -> Self
+    where
+        F: Into<String>,
+        P: Into<String>,
+    {
+        self.condition(Condition::StartsWith(field.into(), prefix.into()))
+    }
+
+    pub fn success_action_redirect<U>(self, url: U) -> Self
+    where
+        U: Into<String>,
+    {
+        self.field("success_action_redirect", url)
+    }
+
+    pub fn success_action_status(self, status: http::StatusCode) -> Self {
+        self.field("success_action_status", status.as_str())
+    }
+
+    // defaults to the current time
+    pub fn time(mut self, time: OffsetDateTime) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn url_style(mut self, url_style: UrlStyle) -> Self {
+        self.url_style = url_style;
+        self
+    }
+}
+
+impl Condition {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::ContentLengthRange(min, max) => {
+                serde_json::json!(["content-length-range", min, max])
+            }
+            Self::Eq(field, value) => serde_json::json!({ field: value }),
+            Self::StartsWith(field, prefix) => {
+                serde_json::json!(["starts-with", format!("${field}"), prefix])
+            }
+        }
+    }
+}
+
+impl Prepared {
+    fn finish(self, signature: &[u8]) -> PostPolicy {
+        let Self {
+            url,
+            mut fields,
+            policy,
+        } = self;
+        fields.push(("x-goog-signature".to_owned(), hex::encode(signature)));
+        fields.push(("policy".to_owned(), policy));
+        PostPolicy { url, fields }
+    }
+}
+
+// the policy document is signed as ASCII
+fn escape_non_ascii(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                escaped.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_sign_with_key() {
+        let key = crate::signature::ServiceAccountKey::from_pem(
+            "test@example.iam.gserviceaccount.com",
+            include_str!("signed_url/test_key.pem"),
+        )
+        .unwrap();
+        let policy = super::builder("my-bucket", "uploads/${filename}")
+            .expiration(Duration::from_secs(600))
+            .starts_with("key", "uploads/")
+            .content_length_range(0, 1048576)
+            .content_type(&mime::IMAGE_PNG)
+            .metadata("owner", "ユーザー")
+            .success_action_status(http::StatusCode::CREATED)
+            .time(datetime!(2020-03-26 00:00:00 UTC))
+            .sign_with_key(&key)
+            .unwrap();
+        assert_eq!(policy.url, "https://storage.googleapis.com/my-bucket/");
+        let fields = policy
+            .fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                ("key", "uploads/${filename}"),
+                ("x-goog-algorithm", "GOOG4-RSA-SHA256"),
+                ("x-goog-credential", "test@example.iam.gserviceaccount.com/20200326/auto/storage/goog4_request"),
+                ("x-goog-date", "20200326T000000Z"),
+                ("Content-Type", "image/png"),
+                ("x-goog-meta-owner", "ユーザー"),
+                ("success_action_status", "201"),
+                ("x-goog-signature", "881ba2a4d1920641eb3d38f7e11df3fc7f8719ff2f88f7956f7d2952f75566a289320a94e33e2a610268ea4db347e82e299623056ab039902d39272a60807941faa648f17356019c986988647b32d19cfcba6ec59106782ee521a9be6554d18362c7159a5c84c4ebc1adc5d98b28c212e1da3a2f920c939d862a66bc02dfc0c2865ce5d00c6018f547f73f8a168012d8cef7f7110ac1f1ca5ba21bc19968ea6ed1f41cc54e6cbe1349ceac343876fc70c5ecf2a7d4f6e5a8844a34ef9301081cfd12703a4131725bf4b4d3edd520948e57b68e1f7515f94cf52ed8ca99542944a3f4c23bd4d43ed8e68846a71e7a6e72214326fb4b1247824fa65d4aa19843ab"),
+                ("policy", "eyJjb25kaXRpb25zIjpbWyJzdGFydHMtd2l0aCIsIiRrZXkiLCJ1cGxvYWRzLyJdLFsiY29udGVudC1sZW5ndGgtcmFuZ2UiLDAsMTA0ODU3Nl0seyJidWNrZXQiOiJteS1idWNrZXQifSx7IngtZ29vZy1hbGdvcml0aG0iOiJHT09HNC1SU0EtU0hBMjU2In0seyJ4LWdvb2ctY3JlZGVudGlhbCI6InRlc3RAZXhhbXBsZS5pYW0uZ3NlcnZpY2VhY2NvdW50LmNvbS8yMDIwMDMyNi9hdXRvL3N0b3JhZ2UvZ29vZzRfcmVxdWVzdCJ9LHsieC1nb29nLWRhdGUiOiIyMDIwMDMyNlQwMDAwMDBaIn0seyJDb250ZW50LVR5cGUiOiJpbWFnZS9wbmcifSx7IngtZ29vZy1tZXRhLW93bmVyIjoiXHUzMGU2XHUzMGZjXHUzMGI2XHUzMGZjIn0seyJzdWNjZXNzX2FjdGlvbl9zdGF0dXMiOiIyMDEifV0sImV4cGlyYXRpb24iOiIyMDIwLTAzLTI2VDAwOjEwOjAwWiJ9"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sign_with_hmac() {
+        let credentials = crate::hmac::Credentials {
+            access_id: "GOOG1EXAMPLE".to_owned(),
+            secret: "bGoa+V7g/yqDXvKRqq+JTFn4uQZbPiQJo4pf9RzJ".to_owned(),
+        };
+        let policy = super::builder("my-bucket", "my-object")
+            .success_action_redirect("https://example.com/done")
+            .url_style(crate::signed_url::UrlStyle::VirtualHostedStyle)
+            .time(datetime!(2020-03-26 00:00:00 UTC))
+            .sign_with_hmac(&credentials)
+            .unwrap();
+        assert_eq!(policy.url, "https://my-bucket.storage.googleapis.com/");
+        let fields = policy
+            .fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                ("key", "my-object"),
+                ("x-goog-algorithm", "GOOG4-HMAC-SHA256"),
+                ("x-goog-credential", "GOOG1EXAMPLE/20200326/auto/storage/goog4_request"),
+                ("x-goog-date", "20200326T000000Z"),
+                ("success_action_redirect", "https://example.com/done"),
+                ("x-goog-signature", "371af83ee8d67085e87d212823628eb8f45ef3ba9807313e38bf99c46f46ad74"),
+                ("policy", "eyJjb25kaXRpb25zIjpbeyJidWNrZXQiOiJteS1idWNrZXQifSx7ImtleSI6Im15LW9iamVjdCJ9LHsieC1nb29nLWFsZ29yaXRobSI6IkdPT0c0LUhNQUMtU0hBMjU2In0seyJ4LWdvb2ctY3JlZGVudGlhbCI6IkdPT0cxRVhBTVBMRS8yMDIwMDMyNi9hdXRvL3N0b3JhZ2UvZ29vZzRfcmVxdWVzdCJ9LHsieC1nb29nLWRhdGUiOiIyMDIwMDMyNlQwMDAwMDBaIn0seyJzdWNjZXNzX2FjdGlvbl9yZWRpcmVjdCI6Imh0dHBzOi8vZXhhbXBsZS5jb20vZG9uZSJ9XSwiZXhwaXJhdGlvbiI6IjIwMjAtMDMtMjZUMDE6MDA6MDBaIn0="),
+            ],
+        );
+    }
+
+    // the expected documents are written out from
+    // https://cloud.google.com/storage/docs/authentication/signatures#policy-document rather than
+    // taken from the output of the code under test
+    #[test]
+    fn test_policy_document() {
+        let key = crate::signature::ServiceAccountKey::from_pem(
+            "test@example.iam.gserviceaccount.com",
+            include_str!("signed_url/test_key.pem"),
+        )
+        .unwrap();
+        let public_key = rsa::RsaPublicKey::from(
+            <rsa::RsaPrivateKey as rsa::pkcs8::DecodePrivateKey>::from_pkcs8_pem(include_str!(
+                "signed_url/test_key.pem"
+            ))
+            .unwrap(),
+        );
+        let policy = super::builder("my-bucket", "uploads/${filename}")
+            .expiration(Duration::from_secs(600))
+            .starts_with("key", "uploads/")
+            .content_length_range(0, 1048576)
+            .metadata("owner", "ユーザー")
+            .time(datetime!(2020-03-26 00:00:00 UTC))
+            .sign_with_key(&key)
+            .unwrap();
+        let field = |name| {
+            policy
+                .fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+                .unwrap()
+        };
+        // non-ASCII characters are escaped before encoding
+        let document =
+            base64::Engine::decode(&base64::prelude::BASE64_STANDARD, field("policy")).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&document).unwrap(),
+            concat!(
+                r#"{"conditions":["#,
+                r#"["starts-with","$key","uploads/"],"#,
+                r#"["content-length-range",0,1048576],"#,
+                r#"{"bucket":"my-bucket"},"#,
+                r#"{"x-goog-algorithm":"GOOG4-RSA-SHA256"},"#,
+                r#"{"x-goog-credential":"test@example.iam.gserviceaccount.com/20200326/auto/storage/goog4_request"},"#,
+                r#"{"x-goog-date":"20200326T000000Z"},"#,
+                r#"{"x-goog-meta-owner":"\u30e6\u30fc\u30b6\u30fc"}"#,
+                r#"],"expiration":"2020-03-26T00:10:00Z"}"#,
+            ),
+        );
+        // the base64-encoded document is signed as is
+        public_key
+            .verify(
+                rsa::Pkcs1v15Sign::new::<sha2::Sha256>(),
+                &<sha2::Sha256 as sha2::Digest>::digest(field("policy")),
+                &hex::decode(field("x-goog-signature")).unwrap(),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hmac_signature() {
+        use ::hmac::Mac;
+
+        fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+            let mut mac = ::hmac::Hmac::<sha2::Sha256>::new_from_slice(key).unwrap();
+            mac.update(data.as_bytes());
+            mac.finalize().into_bytes().to_vec()
+        }
+
+        let credentials = crate::hmac::Credentials {
+            access_id: "GOOG1EXAMPLE".to_owned(),
+            secret: "bGoa+V7g/yqDXvKRqq+JTFn4uQZbPiQJo4pf9RzJ".to_owned(),
+        };
+        let policy = super::builder("my-bucket", "my-object")
+            .time(datetime!(2020-03-26 00:00:00 UTC))
+            .sign_with_hmac(&credentials)
+            .unwrap();
+        let field = |name| {
+            policy
+                .fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+                .unwrap()
+        };
+        // https://cloud.google.com/storage/docs/authentication/signatures#signing-process
+        let key = hmac_sha256(
+            format!("GOOG4{}", credentials.secret).as_bytes(),
+            "20200326",
+        );
+        let key = hmac_sha256(&key, "auto");
+        let key = hmac_sha256(&key, "storage");
+        let key = hmac_sha256(&key, "goog4_request");
+        assert_eq!(
+            field("x-goog-signature"),
+            hex::encode(hmac_sha256(&key, field("policy"))),
+        );
+    }
+
+    #[test]
+    fn test_expiration_time() {
+        let credentials = crate::hmac::Credentials {
+            access_id: "GOOG1EXAMPLE".to_owned(),
+            secret: "secret".to_owned(),
+        };
+        for time in [
+            datetime!(9999-12-31 23:59:59 UTC),
+            datetime!(-0001-01-01 00:00:00 UTC),
+        ] {
+            assert!(matches!(
+                super::builder("my-bucket", "my-object")
+                    .time(time)
+                    .sign_with_hmac(&credentials),
+                Err(super::Error::ExpirationTime),
+            ));
+        }
+    }
+}
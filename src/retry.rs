@@ -0,0 +1,215 @@
+// delays between the attempts of the helpers that poll or retry requests, and which failures are
+// worth retrying
+
+use crate::api;
+use http::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::convert::Infallible;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+// the shortest delay handed out, so that a zero initial delay or multiplier does not busy-loop
+const MIN_DELAY: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            multiplier: 2,
+        }
+    }
+}
+
+impl Backoff {
+    // initial, initial * multiplier, ... up to max, and never below 100ms
+    pub fn delays(self) -> Delays {
+        Delays {
+            backoff: self,
+            next: self.initial,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Delays {
+    backoff: Backoff,
+    next: Duration,
+}
+
+impl Iterator for Delays {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let max = self.backoff.max.max(MIN_DELAY);
+        let delay = self.next.clamp(MIN_DELAY, max);
+        self.next = delay.saturating_mul(self.backoff.multiplier.max(1));
+        Some(delay)
+    }
+}
+
+// a random delay between half of and all of delay, so that clients that conflict with each other
+// spread out
+pub fn jitter(delay: Duration) -> Duration {
+    // every RandomState is seeded differently, which is random enough to spread out retries
+    let random = RandomState::new().build_hasher().finish();
+    let half = delay / 2;
+    let nanos = ((delay - half).as_nanos() * u128::from(random)) >> 64;
+    half.saturating_add(Duration::from_nanos(
+        u64::try_from(nanos).unwrap_or(u64::MAX),
+    ))
+}
+
+// https://cloud.google.com/storage/docs/retry-strategy
+// whether the same request may succeed if it is sent again
+pub trait Transient {
+    fn is_transient(&self) -> bool;
+}
+
+// 408 Request Timeout, 429 Too Many Requests and 5xx except 501 Not Implemented
+pub fn is_transient_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502..=599)
+}
+
+// the service and body errors are failures to reach GCS or to read its response. the others are
+// about the request or response itself and happen again on a retry.
+impl<S, B> Transient for api::Error<S, B> {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Body(_) | Self::Service(_) => true,
+            Self::Status(api::StatusError(response)) => is_transient_status(response.status()),
+            Self::Http(_) | Self::Json(_) | Self::XmlDe(_) | Self::XmlSe(_) => false,
+        }
+    }
+}
+
+impl<P, S> Transient for crate::auth::Error<P, S>
+where
+    P: Transient,
+{
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::InvalidBearerToken(_) => false,
+            Self::Service(_) => true,
+            Self::TokenProvider(e) => e.is_transient(),
+        }
+    }
+}
+
+impl<E> Transient for Arc<E>
+where
+    E: Transient,
+{
+    fn is_transient(&self) -> bool {
+        E::is_transient(self)
+    }
+}
+
+impl Transient for Infallible {
+    fn is_transient(&self) -> bool {
+        match *self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backoff, Transient};
+    use crate::api;
+    use http::StatusCode;
+    use std::time::Duration;
+
+    #[test]
+    fn test_delays() {
+        let delays = Backoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(5),
+            multiplier: 2,
+        }
+        .delays();
+        assert_eq!(
+            delays.take(5).collect::<Vec<_>>(),
+            [1, 2, 4, 5, 5].map(Duration::from_secs),
+        );
+
+        // a zero delay or multiplier still waits
+        let delays = Backoff {
+            initial: Duration::ZERO,
+            max: Duration::ZERO,
+            multiplier: 0,
+        }
+        .delays();
+        assert_eq!(
+            delays.take(2).collect::<Vec<_>>(),
+            [Duration::from_millis(100); 2],
+        );
+
+        // saturates instead of overflowing
+        let delays = Backoff {
+            initial: Duration::MAX,
+            max: Duration::MAX,
+            multiplier: u32::MAX,
+        }
+        .delays();
+        assert_eq!(delays.take(2).collect::<Vec<_>>(), [Duration::MAX; 2]);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let delay = Duration::from_secs(10);
+        let delays = (0..100).map(|_| super::jitter(delay)).collect::<Vec<_>>();
+        assert!(delays
+            .iter()
+            .all(|jittered| (delay / 2..=delay).contains(jittered)));
+        assert!(delays.iter().any(|jittered| *jittered != delays[0]));
+        assert_eq!(super::jitter(Duration::ZERO), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_is_transient_status() {
+        for (status, expected) in [
+            (200, false),
+            (308, false),
+            (400, false),
+            (401, false),
+            (403, false),
+            (404, false),
+            (408, true),
+            (409, false),
+            (412, false),
+            (429, true),
+            (500, true),
+            (501, false),
+            (502, true),
+            (503, true),
+            (504, true),
+            (505, true),
+            (599, true),
+        ] {
+            let status = StatusCode::from_u16(status).unwrap();
+            assert_eq!(super::is_transient_status(status), expected, "{status}");
+
+            let e = api::Error::<(), ()>::Status(api::StatusError(
+                http::Response::builder()
+                    .status(status)
+                    .body(bytes::Bytes::new())
+                    .unwrap(),
+            ));
+            assert_eq!(e.is_transient(), expected, "{status}");
+        }
+
+        assert!(api::Error::<(), ()>::Service(()).is_transient());
+        assert!(api::Error::<(), ()>::Body(()).is_transient());
+        let e = serde_json::from_str::<u64>("").unwrap_err();
+        assert!(!api::Error::<(), ()>::Json(e).is_transient());
+        let e = api::Error::<(), ()>::Service(());
+        assert!(std::sync::Arc::new(e).is_transient());
+    }
+}
@@ -0,0 +1,280 @@
+// https://cloud.google.com/storage/docs/authentication/signatures
+
+use crate::api::iam_credentials::sign_blob;
+use futures::future::MapOk;
+use futures::TryFutureExt;
+use rsa::pkcs8::DecodePrivateKey;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::future::{self, Future, Ready};
+use std::marker::PhantomData;
+use time::{OffsetDateTime, UtcOffset};
+
+const UNRESERVED: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+// unreserved characters and '/' are left as-is in object names
+pub(crate) const PATH: &percent_encoding::AsciiSet = &UNRESERVED.remove(b'/');
+
+pub(crate) const REGION: &str = "auto";
+pub(crate) const SERVICE: &str = "storage";
+pub(crate) const REQUEST_TYPE: &str = "goog4_request";
+
+// YYYYMMDD'T'HHMMSS'Z'
+pub(crate) fn timestamp(time: OffsetDateTime) -> String {
+    let time = time.to_offset(UtcOffset::UTC);
+    format!(
+        "{}T{:02}{:02}{:02}Z",
+        date(time),
+        time.hour(),
+        time.minute(),
+        time.second(),
+    )
+}
+
+// YYYYMMDD
+pub(crate) fn date(time: OffsetDateTime) -> String {
+    let time = time.to_offset(UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+    )
+}
+
+pub(crate) fn scope(time: OffsetDateTime) -> String {
+    format!("{}/{REGION}/{SERVICE}/{REQUEST_TYPE}", date(time))
+}
+
+pub(crate) fn encode(value: &[u8]) -> String {
+    percent_encoding::percent_encode(value, UNRESERVED).to_string()
+}
+
+pub(crate) fn canonical_query<I, K, V>(query: I) -> String
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let mut query = query
+        .into_iter()
+        .map(|(key, value)| (encode(key.as_ref()), encode(value.as_ref())))
+        .collect::<Vec<_>>();
+    query.sort();
+    query
+        .into_iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+pub(crate) fn decode_query(query: &str) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (
+                percent_encoding::percent_decode_str(key).collect(),
+                percent_encoding::percent_decode_str(value).collect(),
+            )
+        })
+}
+
+// header names must be lowercase
+pub(crate) fn canonical_headers<'a, I>(headers: I) -> (String, String)
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut map = BTreeMap::<_, Vec<_>>::new();
+    for (name, value) in headers {
+        map.entry(name)
+            .or_default()
+            .push(value.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    let canonical_headers = map
+        .iter()
+        .map(|(name, values)| format!("{name}:{}\n", values.join(",")))
+        .collect();
+    let signed_headers = map.into_keys().collect::<Vec<_>>().join(";");
+    (canonical_headers, signed_headers)
+}
+
+// the path of a request as GCS sees it, whichever characters the client chose to escape
+pub(crate) fn canonical_path(path: &str) -> String {
+    let path = percent_encoding::percent_decode_str(path).collect::<Vec<_>>();
+    percent_encoding::percent_encode(&path, PATH).to_string()
+}
+
+pub(crate) fn canonical_request(
+    method: &http::Method,
+    path: &str,
+    canonical_query: &str,
+    canonical_headers: &str,
+    signed_headers: &str,
+    payload: &str,
+) -> String {
+    format!("{method}\n{path}\n{canonical_query}\n{canonical_headers}\n{signed_headers}\n{payload}")
+}
+
+pub(crate) fn string_to_sign(
+    algorithm: &str,
+    time: OffsetDateTime,
+    canonical_request: &str,
+) -> String {
+    format!(
+        "{algorithm}\n{}\n{}\n{}",
+        timestamp(time),
+        scope(time),
+        hex_sha256(canonical_request),
+    )
+}
+
+pub(crate) fn hex_sha256<T>(data: T) -> String
+where
+    T: AsRef<[u8]>,
+{
+    hex::encode(Sha256::digest(data))
+}
+
+pub const RSA_ALGORITHM: &str = "GOOG4-RSA-SHA256";
+
+// produces RSASSA-PKCS1-v1_5 SHA-256 signatures on behalf of a service account
+pub trait Signer {
+    type Error;
+    type Future: Future<Output = Result<Vec<u8>, Self::Error>>;
+
+    fn client_email(&self) -> &str;
+    fn sign(&self, message: &[u8]) -> Self::Future;
+}
+
+// https://cloud.google.com/iam/docs/keys-create-delete
+#[derive(Clone)]
+pub struct ServiceAccountKey {
+    pub client_email: String,
+    private_key: rsa::RsaPrivateKey,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeyError {
+    #[error(transparent)]
+    Json(serde_json::Error),
+    #[error(transparent)]
+    Pkcs8(rsa::pkcs8::Error),
+}
+
+impl ServiceAccountKey {
+    // PKCS#8 PEM, as in the private_key field of a key file
+    pub fn from_pem<E>(client_email: E, pem: &str) -> Result<Self, KeyError>
+    where
+        E: Into<String>,
+    {
+        Ok(Self {
+            client_email: client_email.into(),
+            private_key: rsa::RsaPrivateKey::from_pkcs8_pem(pem).map_err(KeyError::Pkcs8)?,
+        })
+    }
+
+    pub fn from_json(json: &[u8]) -> Result<Self, KeyError> {
+        #[derive(serde::Deserialize)]
+        struct KeyFile {
+            client_email: String,
+            private_key: String,
+        }
+
+        let KeyFile {
+            client_email,
+            private_key,
+        } = serde_json::from_slice(json).map_err(KeyError::Json)?;
+        Self::from_pem(client_email, &private_key)
+    }
+
+    // RSASSA-PKCS1-v1_5 with SHA-256
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, rsa::Error> {
+        self.private_key
+            .sign(rsa::Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(message))
+    }
+}
+
+impl Signer for ServiceAccountKey {
+    type Error = rsa::Error;
+    type Future = Ready<Result<Vec<u8>, Self::Error>>;
+
+    fn client_email(&self) -> &str {
+        &self.client_email
+    }
+
+    fn sign(&self, message: &[u8]) -> Self::Future {
+        future::ready(Self::sign(self, message))
+    }
+}
+
+// signs with the Google-managed key of a service account through signBlob,
+// e.g. with tokens from the metadata server where no private key is available
+pub struct IamSigner<S, T> {
+    service: S,
+    client_email: String,
+    delegates: Vec<String>,
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<S, T> Clone for IamSigner<S, T>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            client_email: self.client_email.clone(),
+            delegates: self.delegates.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S, T> IamSigner<S, T> {
+    pub fn new<E>(service: S, client_email: E) -> Self
+    where
+        E: Into<String>,
+    {
+        Self {
+            service,
+            client_email: client_email.into(),
+            delegates: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn delegates(mut self, delegates: Vec<String>) -> Self {
+        self.delegates = delegates;
+        self
+    }
+}
+
+impl<S, T, U> Signer for IamSigner<S, T>
+where
+    S: Clone + tower::Service<http::Request<T>, Response = http::Response<U>>,
+    T: From<String>,
+    U: http_body::Body,
+{
+    type Error = crate::api::Error<S::Error, U::Error>;
+    type Future =
+        MapOk<sign_blob::Future<S, T, U>, fn(http::Response<sign_blob::Response>) -> Vec<u8>>;
+
+    fn client_email(&self) -> &str {
+        &self.client_email
+    }
+
+    fn sign(&self, message: &[u8]) -> Self::Future {
+        let map_ok: fn(http::Response<sign_blob::Response>) -> Vec<u8> =
+            |response| response.into_body().signed_blob;
+        sign_blob::builder(&self.client_email, message.to_vec())
+            .delegates(self.delegates.clone())
+            .send(self.service.clone())
+            .map_ok(map_ok)
+    }
+}
@@ -1,132 +1,103 @@
+use crate::auth::impersonated::Impersonated;
+use crate::auth::{Cached, Token, TokenProvider};
+use crate::retry::Transient;
 use futures::future::BoxFuture;
-use futures::FutureExt;
-use headers::{Authorization, HeaderMapExt};
-use http::Request;
+use futures::{FutureExt, TryFutureExt};
 use hyper_util::client::legacy::connect::Connect;
 use std::env;
-use std::future;
-use std::mem;
-use std::pin::Pin;
-use std::task::{ready, Context, Poll};
+use std::io;
+use std::sync::Arc;
+use tower::Layer as _;
 use yup_oauth2::authenticator::{
     ApplicationDefaultCredentialsTypes, Authenticator, HyperClientBuilder,
 };
 
-#[derive(Debug, thiserror::Error)]
-pub enum Error<S> {
-    #[error(transparent)]
-    Authenticator(yup_oauth2::Error),
-    #[error(transparent)]
-    InvalidBearerToken(headers::authorization::InvalidBearerToken),
-    #[error(transparent)]
-    Service(S),
-}
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Error<S> = crate::auth::Error<Arc<ProviderError>, S>;
+pub type Service<S, C> = crate::auth::Service<S, Cached<Provider<C>>>;
+pub type Layer<C> = crate::auth::Layer<Cached<Provider<C>>>;
+
+// an Authenticator, or generateAccessToken through the delegates of an impersonated service
+// account, which yup-oauth2 does not support, with the Authenticator of its source credentials
+#[derive(Clone)]
+pub struct Provider<C>(Inner<C>);
 
 #[derive(Clone)]
-pub struct Service<S, C> {
-    inner: S,
-    authenticator: Authenticator<C>,
+enum Inner<C> {
+    Authenticator(Authenticator<C>),
+    Impersonated(Arc<ImpersonatedFn>),
 }
 
-impl<S, C, B> tower::Service<Request<B>> for Service<S, C>
-where
-    S: Clone + tower::Service<Request<B>>,
-    C: Clone + Connect + Send + Sync + 'static,
-{
-    type Response = S::Response;
-    type Error = Error<S::Error>;
-    type Future = Future<S, B>;
+type ImpersonatedFn =
+    dyn Fn(&[String]) -> BoxFuture<'static, Result<Token, ProviderError>> + Send + Sync;
 
-    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.inner.poll_ready(cx).map_err(Error::Service)
-    }
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    #[error(transparent)]
+    Authenticator(#[from] yup_oauth2::Error),
+    #[error("generateAccessToken through delegates failed")]
+    Impersonated {
+        #[source]
+        source: BoxError,
+        transient: bool,
+    },
+}
 
-    fn call(&mut self, request: Request<B>) -> Self::Future {
-        let inner = self.inner.clone();
-        let inner = mem::replace(&mut self.inner, inner);
-        let authenticator = self.authenticator.clone();
-        let f = async move {
-            authenticator
-                .token(&["https://www.googleapis.com/auth/cloud-platform"])
-                .await
+impl Transient for ProviderError {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Authenticator(e) => e.is_transient(),
+            Self::Impersonated { transient, .. } => *transient,
         }
-        .boxed();
-        Future(State::S0 {
-            f,
-            inner,
-            request: Some(request),
-        })
     }
 }
 
-#[pin_project::pin_project]
-pub struct Future<S, B>(#[pin] State<S, B>)
+impl<C> TokenProvider for Provider<C>
 where
-    S: tower::Service<Request<B>>;
-
-#[pin_project::pin_project(project = StateProj)]
-enum State<S, B>
-where
-    S: tower::Service<Request<B>>,
+    C: Clone + Connect + Send + Sync + 'static,
 {
-    S0 {
-        #[pin]
-        f: BoxFuture<'static, Result<yup_oauth2::AccessToken, yup_oauth2::Error>>,
-        inner: S,
-        request: Option<Request<B>>,
-    },
-    S1 {
-        #[pin]
-        f: S::Future,
-    },
-}
+    type Error = ProviderError;
+    type Future = BoxFuture<'static, Result<Token, Self::Error>>;
 
-impl<S, B> future::Future for Future<S, B>
-where
-    S: tower::Service<Request<B>>,
-{
-    type Output = Result<S::Response, Error<S::Error>>;
-
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let mut this = self.project();
-        loop {
-            match this.0.as_mut().project() {
-                StateProj::S0 { f, inner, request } => {
-                    let access_token = ready!(f.poll(cx)).map_err(Error::Authenticator)?;
-                    let token = access_token
-                        .token()
-                        .ok_or(Error::Authenticator(yup_oauth2::Error::MissingAccessToken))?;
-                    let header = Authorization::bearer(token).map_err(Error::InvalidBearerToken)?;
-                    let mut request = request.take().unwrap();
-                    request.headers_mut().typed_insert(header);
-                    let f = inner.call(request);
-                    this.0.set(State::S1 { f });
-                }
-                StateProj::S1 { f } => {
-                    let response = ready!(f.poll(cx)).map_err(Error::Service)?;
-                    break Poll::Ready(Ok(response));
-                }
-            }
+    fn token(&self, scopes: &[String]) -> Self::Future {
+        match &self.0 {
+            Inner::Authenticator(authenticator) => TokenProvider::token(authenticator, scopes)
+                .map_err(ProviderError::from)
+                .boxed(),
+            Inner::Impersonated(f) => f(scopes),
         }
     }
 }
 
-#[derive(Clone)]
-pub struct Layer<C> {
-    authenticator: Authenticator<C>,
-}
-
-impl<S, C> tower::Layer<S> for Layer<C>
+impl<C> TokenProvider for Authenticator<C>
 where
-    C: Clone,
+    C: Clone + Connect + Send + Sync + 'static,
 {
-    type Service = Service<S, C>;
+    type Error = yup_oauth2::Error;
+    type Future = BoxFuture<'static, Result<Token, Self::Error>>;
 
-    fn layer(&self, inner: S) -> Self::Service {
-        Service {
-            inner,
-            authenticator: self.authenticator.clone(),
+    fn token(&self, scopes: &[String]) -> Self::Future {
+        let authenticator = self.clone();
+        let scopes = scopes.to_vec();
+        async move {
+            let access_token = authenticator.token(&scopes).await?;
+            let token = access_token
+                .token()
+                .ok_or(yup_oauth2::Error::MissingAccessToken)?;
+            Ok(Token {
+                access_token: token.to_owned(),
+                expires_at: access_token.expiration_time(),
+            })
         }
+        .boxed()
+    }
+}
+
+// failures to reach the token endpoint, as opposed to credentials that were rejected
+impl Transient for yup_oauth2::Error {
+    fn is_transient(&self) -> bool {
+        matches!(self, Self::HttpError(_) | Self::LowLevelError(_))
     }
 }
 
@@ -134,20 +105,64 @@ impl<C> Layer<C>
 where
     C: Clone + Connect + Send + Sync + 'static,
 {
-    pub async fn with_client<Client>(client: Client) -> Result<Self, yup_oauth2::Error>
+    pub async fn with_client<Client, U>(client: Client) -> Result<Self, yup_oauth2::Error>
     where
-        Client: HyperClientBuilder<Connector = C>,
+        Client: Clone
+            + HyperClientBuilder<Connector = C>
+            + tower::Service<http::Request<String>, Response = http::Response<U>>
+            + Send
+            + Sync
+            + 'static,
+        Client::Future: Send,
+        Client::Error: std::error::Error + Send + Sync + 'static,
+        U: http_body::Body + Send + 'static,
+        U::Data: Send,
+        U::Error: std::error::Error + Send + Sync + 'static,
     {
-        let authenticator = async {
+        let provider = async {
             if let Ok(path) = env::var("GOOGLE_APPLICATION_CREDENTIALS") {
-                if let Ok(secret) = yup_oauth2::read_authorized_user_secret(&path).await {
+                if let Some(secret) = read_impersonated_service_account_secret(&path).await? {
+                    if secret.delegates.is_empty() {
+                        return yup_oauth2::ServiceAccountImpersonationAuthenticator::with_client(
+                            secret.source_credentials,
+                            &secret.client_email,
+                            client,
+                        )
+                        .build()
+                        .await
+                        .map(Inner::Authenticator);
+                    }
+                    let source = yup_oauth2::AuthorizedUserAuthenticator::with_client(
+                        secret.source_credentials,
+                        client.clone(),
+                    )
+                    .build()
+                    .await?;
+                    let service = crate::auth::Layer::new(Cached::new(Provider(
+                        Inner::Authenticator(source),
+                    )))
+                    .layer(client);
+                    let impersonated = Impersonated::<_, String>::new(service, secret.client_email)
+                        .delegates(secret.delegates);
+                    return Ok(Inner::Impersonated(Arc::new(move |scopes: &[String]| {
+                        impersonated
+                            .token(scopes)
+                            .map_err(|e| ProviderError::Impersonated {
+                                transient: e.is_transient(),
+                                source: e.into(),
+                            })
+                            .boxed()
+                    })));
+                } else if let Ok(secret) = yup_oauth2::read_authorized_user_secret(&path).await {
                     return yup_oauth2::AuthorizedUserAuthenticator::with_client(secret, client)
                         .build()
-                        .await;
+                        .await
+                        .map(Inner::Authenticator);
                 } else if let Ok(secret) = yup_oauth2::read_external_account_secret(&path).await {
                     return yup_oauth2::ExternalAccountAuthenticator::with_client(secret, client)
                         .build()
-                        .await;
+                        .await
+                        .map(Inner::Authenticator);
                 }
             }
             match yup_oauth2::ApplicationDefaultCredentialsAuthenticator::with_client(
@@ -163,8 +178,143 @@ where
                     builder.build().await
                 }
             }
+            .map(Inner::Authenticator)
         }
         .await?;
-        Ok(Layer { authenticator })
+        Ok(Layer::new(Cached::new(Provider(provider))))
+    }
+}
+
+// the file written by `gcloud auth application-default login --impersonate-service-account`.
+// yup-oauth2 impersonates from user credentials only, without delegates.
+struct ImpersonatedServiceAccountSecret {
+    client_email: String,
+    // projects/-/serviceAccounts/{email}
+    delegates: Vec<String>,
+    source_credentials: yup_oauth2::AuthorizedUserSecret,
+}
+
+async fn read_impersonated_service_account_secret(
+    path: &str,
+) -> io::Result<Option<ImpersonatedServiceAccountSecret>> {
+    #[derive(serde::Deserialize)]
+    struct File {
+        #[serde(default)]
+        delegates: Vec<String>,
+        service_account_impersonation_url: String,
+        source_credentials: serde_json::Value,
+        #[serde(rename = "type")]
+        key_type: String,
+    }
+
+    // anything else is left to the other credential types
+    let Ok(File {
+        delegates,
+        service_account_impersonation_url,
+        source_credentials,
+        key_type,
+    }) = tokio::fs::read(path)
+        .await
+        .and_then(|data| Ok(serde_json::from_slice(&data)?))
+    else {
+        return Ok(None);
+    };
+    if key_type != "impersonated_service_account" {
+        return Ok(None);
+    }
+    // https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/{email}:generateAccessToken
+    let client_email = service_account_impersonation_url
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.strip_suffix(":generateAccessToken"))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "impersonated_service_account: invalid service_account_impersonation_url",
+            )
+        })?;
+    let source_credentials = serde_json::from_value(source_credentials).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("impersonated_service_account: unsupported source_credentials: {e}"),
+        )
+    })?;
+    // gcloud writes the emails of the delegates
+    let delegates = delegates
+        .into_iter()
+        .map(|delegate| {
+            if delegate.starts_with("projects/") {
+                delegate
+            } else {
+                format!("projects/-/serviceAccounts/{delegate}")
+            }
+        })
+        .collect();
+    Ok(Some(ImpersonatedServiceAccountSecret {
+        client_email: client_email.to_owned(),
+        delegates,
+        source_credentials,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    #[tokio::test]
+    async fn test_read_impersonated_service_account_secret() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("application_default_credentials.json");
+        let path = path.to_str().unwrap();
+
+        std::fs::write(
+            path,
+            serde_json::json!({
+                "delegates": [
+                    "ci@p.iam.gserviceaccount.com",
+                    "projects/-/serviceAccounts/build@p.iam.gserviceaccount.com",
+                ],
+                "service_account_impersonation_url": "https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/deploy@p.iam.gserviceaccount.com:generateAccessToken",
+                "source_credentials": {
+                    "client_id": "id",
+                    "client_secret": "secret",
+                    "refresh_token": "token",
+                    "type": "authorized_user",
+                },
+                "type": "impersonated_service_account",
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let secret = super::read_impersonated_service_account_secret(path)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(secret.client_email, "deploy@p.iam.gserviceaccount.com");
+        assert_eq!(
+            secret.delegates,
+            [
+                "projects/-/serviceAccounts/ci@p.iam.gserviceaccount.com",
+                "projects/-/serviceAccounts/build@p.iam.gserviceaccount.com",
+            ],
+        );
+        assert_eq!(secret.source_credentials.client_id, "id");
+
+        std::fs::write(
+            path,
+            serde_json::json!({
+                "client_id": "id",
+                "client_secret": "secret",
+                "refresh_token": "token",
+                "type": "authorized_user",
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert!(super::read_impersonated_service_account_secret(path)
+            .await
+            .unwrap()
+            .is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }